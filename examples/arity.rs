@@ -0,0 +1,37 @@
+use std::time::Instant;
+use topset::DaryTopSet;
+
+// compares the running times of binary and 4-ary heaps (the same top set, only the arity differs)
+// (run with `cargo run --release --example arity`)
+fn main()
+{
+    let mut state = 42u64;
+    let items = (0..10_000_000).map(|_| {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        state >> 11
+    }).collect::<Vec<_>>();
+
+    for n in [10, 1_000, 100_000] {
+        let start = Instant::now();
+        let binary = DaryTopSet::<u64,_,2>::with_init(n, u64::gt, items.iter().copied());
+        let binary_time = start.elapsed();
+
+        let start = Instant::now();
+        let quaternary = DaryTopSet::<u64,_,4>::with_init(n, u64::gt, items.iter().copied());
+        let quaternary_time = start.elapsed();
+
+        assert_eq!( binary.peek(), quaternary.peek() );
+        println!("top {n:>6} of {} items: binary {binary_time:?}, 4-ary {quaternary_time:?}", items.len());
+    }
+
+    // the worst case: each insertion evicts the lowest item
+    let n = 100_000;
+    let start = Instant::now();
+    let binary = DaryTopSet::<u64,_,2>::with_init(n, u64::gt, 0..items.len() as u64);
+    let binary_time = start.elapsed();
+    let start = Instant::now();
+    let quaternary = DaryTopSet::<u64,_,4>::with_init(n, u64::gt, 0..items.len() as u64);
+    let quaternary_time = start.elapsed();
+    assert_eq!( binary.peek(), quaternary.peek() );
+    println!("top {n:>6} of ascending items: binary {binary_time:?}, 4-ary {quaternary_time:?}");
+}
@@ -0,0 +1,248 @@
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+use core::mem::{self, MaybeUninit};
+use core::slice;
+use crate::heap::HeapBuffer;
+
+/// A top N set of items stored in a fixed size array.
+///
+/// It behaves as [`crate::TopSet`] but at most `N` items are kept and they are
+/// stored inline, in an array of `N` slots: building and filling this top set
+/// never allocates, which suits real-time code. The capacity is fixed by the type,
+/// so there is no `resize`.
+///
+/// Only [`Self::into_sorted_vec`] allocates (for its result); use [`Self::pop`]
+/// to get the items in order without allocation.
+///
+/// # Example
+/// ```
+/// # use topset::ArrayTopSet;
+/// let mut topset = ArrayTopSet::<u32,_,2>::new(u32::gt);
+/// topset.extend(vec![7,5,6,9,4,2,3]);
+/// assert_eq!( topset.pop(), Some(7));
+/// assert_eq!( topset.pop(), Some(9));
+/// assert_eq!( topset.pop(), None);
+/// ```
+pub struct ArrayTopSet<X,C,const N: usize>
+    where C: Fn(&X,&X) -> bool
+{
+    items: [MaybeUninit<X>; N], // a heap with the greatest at the end (only the `len` first are initialized)
+    len: usize,
+    beat: C
+}
+
+impl<X,C,const N: usize> ArrayTopSet<X,C,N>
+    where C: Fn(&X,&X) -> bool
+{
+    /// Creates a new array top set with a selecting closure.
+    ///
+    /// The capacity is `N`. See [`crate::TopSet::new`] for the meaning of `beat`.
+    pub fn new(beat: C) -> Self
+    {
+        Self {
+            items: [const { MaybeUninit::uninit() }; N],
+            len: 0,
+            beat
+        }
+    }
+
+    /// Creates a new array top set with a selecting closure and an initial set of items.
+    ///
+    /// See [`crate::TopSet::with_init`].
+    pub fn with_init<I: IntoIterator<Item=X>>(beat: C, init: I) -> Self
+    {
+        let mut top = Self::new(beat);
+        top.extend(init);
+        top
+    }
+
+    /// Check if the top set is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+
+    /// Get the number of stored items.
+    #[inline]
+    pub fn len(&self) -> usize { self.len }
+
+    /// Get the capacity of this top set (which is `N`)
+    #[inline]
+    pub fn capacity(&self) -> usize { N }
+
+    /// Read access to the lowest item of the top set
+    #[inline]
+    pub fn peek(&self) -> Option<&X>
+    {
+        self.as_slice().first()
+    }
+
+    /// Checks if an item will be inserted or not
+    #[inline]
+    pub fn is_candidate(&self, x: &X) -> bool {
+        self.len < N || self.peek().is_some_and(|p| (self.beat)(x, p))
+    }
+
+    /// Iterate over all the top selected items (**not** sorted).
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item=&X>
+    {
+        self.as_slice().iter()
+    }
+
+    /// Gets the stored items as a slice (**not** sorted).
+    #[inline]
+    pub fn as_slice(&self) -> &[X]
+    {
+        // SAFETY: the `len` first items are initialized
+        unsafe { slice::from_raw_parts(self.items.as_ptr().cast::<X>(), self.len) }
+    }
+
+    /// Insert a new item.
+    ///
+    /// See [`crate::TopSet::insert`].
+    pub fn insert(&mut self, mut x: X) -> Option<X>
+    {
+        if self.len < N {
+            // some room left, so nothing to remove
+            self.items[self.len].write(x);
+            self.len += 1;
+            self.percolate_up(self.len-1);
+            None
+        } else {
+            if N != 0 && (self.beat)(&x, &self.as_slice()[0]) {
+                // put the greatest the deepest: the new one should be kept
+                // SAFETY: the top set is full, so the root is initialized
+                mem::swap(&mut x, unsafe { self.items[0].assume_init_mut() });
+                self.percolate_down(0);
+            }
+            Some(x)
+        }
+    }
+
+    /// Pop the lowest item of the top set
+    pub fn pop(&mut self) -> Option<X>
+    {
+        if self.len == 0 {
+            None
+        } else {
+            // the last item replaces the root which is moved out of the heap
+            self.len -= 1;
+            self.items.swap(0, self.len);
+            // SAFETY: this slot was initialized and is now ignored
+            let pop = unsafe { self.items[self.len].assume_init_read() };
+            self.percolate_down(0);
+            Some(pop)
+        }
+    }
+
+    /// Returns the topset in a sorted vector.
+    ///
+    /// The first element of the vector is the _lowest_ item of the top set
+    /// and the last one is the _greatest_ one.
+    pub fn into_sorted_vec(mut self) -> Vec<X>
+    {
+        let mut sorted = Vec::with_capacity(self.len);
+        while let Some(x) = self.pop() {
+            sorted.push(x);
+        }
+        sorted
+    }
+
+    /// Removes all the elements in the top set
+    pub fn clear(&mut self)
+    {
+        let len = mem::replace(&mut self.len, 0);
+        // SAFETY: the `len` first items were initialized and are now ignored
+        self.items[..len].iter_mut().for_each(|x| unsafe { x.assume_init_drop() });
+    }
+}
+
+impl<X,C,const N: usize> HeapBuffer for ArrayTopSet<X,C,N>
+    where C: Fn(&X,&X) -> bool
+{
+    #[inline] fn size(&self) -> usize { self.len }
+    #[inline] fn duel(&self, i: usize, j: usize) -> bool { let items = self.as_slice(); (self.beat)(&items[i], &items[j]) }
+    #[inline] fn exchange(&mut self, i: usize, j: usize) { self.items.swap(i, j) }
+}
+
+impl<X,C,const N: usize> Drop for ArrayTopSet<X,C,N>
+    where C: Fn(&X,&X) -> bool
+{
+    fn drop(&mut self) { self.clear() }
+}
+
+impl<X,C,const N: usize> Clone for ArrayTopSet<X,C,N>
+    where X: Clone, C: Fn(&X,&X) -> bool + Clone
+{
+    fn clone(&self) -> Self
+    {
+        let mut top = Self::new(self.beat.clone());
+        // the same layout, so the heap is kept as is
+        for x in self.iter() {
+            top.items[top.len].write(x.clone());
+            top.len += 1;
+        }
+        top
+    }
+}
+
+impl<X,C,const N: usize> Extend<X> for ArrayTopSet<X,C,N>
+    where C: Fn(&X,&X) -> bool
+{
+    #[inline]
+    fn extend<T: IntoIterator<Item=X>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|x| { self.insert(x); } )
+    }
+}
+
+impl<X,C,const N: usize> Debug for ArrayTopSet<X,C,N>
+    where X:Debug, C: Fn(&X,&X) -> bool
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.as_slice().fmt(f)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use crate::{ArrayTopSet, TopSet};
+
+    #[test]
+    fn same_as_vec()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        let expected = TopSet::with_init(5, i32::gt, items.clone()).into_sorted_vec();
+        let mut top = ArrayTopSet::<i32,_,5>::with_init(i32::gt, items.clone());
+        assert_eq![ top.len(), 5 ];
+        assert_eq![ top.peek(), expected.first() ];
+        assert!( top.is_candidate(&100) && !top.is_candidate(&1) );
+        assert_eq![ top.clone().into_sorted_vec(), expected ];
+        assert_eq![ top.insert(100), Some(22) ];
+        assert_eq![ top.into_sorted_vec(), vec![45, 81, 97, 100, 877] ];
+
+        let mut none = ArrayTopSet::<i32,_,0>::with_init(i32::gt, items);
+        assert_eq![ none.insert(1), Some(1) ];
+        assert_eq![ none.pop(), None ];
+    }
+
+    #[test]
+    fn dropped_once()
+    {
+        // each item is counted by a shared counter, released when the item is dropped
+        let counter = Rc::new(());
+        let beat = |a: &(u32, Rc<()>), b: &(u32, Rc<()>)| a.0 > b.0;
+        let mut top = ArrayTopSet::<_,_,4>::new(beat);
+        for i in 0..10 {
+            top.insert((i * 7 % 10, counter.clone()));
+            assert_eq![ Rc::strong_count(&counter), 1 + top.len() ];
+        }
+        let cloned = top.clone();
+        assert_eq![ Rc::strong_count(&counter), 9 ];
+        assert_eq![ top.pop().map(|x| x.0), Some(6) ];
+        drop(cloned);
+        assert_eq![ Rc::strong_count(&counter), 4 ];
+        drop(top);
+        assert_eq![ Rc::strong_count(&counter), 1 ];
+    }
+}
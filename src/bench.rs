@@ -0,0 +1,78 @@
+use alloc::vec::Vec;
+use core::cell::Cell;
+use crate::heap::HeapBuffer;
+use crate::TopSet;
+
+/// Generates a deterministic workload of pseudo-random integers.
+///
+/// The same `seed` always gives the same `len` integers, whatever the platform
+/// (the generator is a plain `splitmix64`), so the benchmarks are reproducible.
+///
+/// # Example
+/// ```
+/// # use topset::bench::workload;
+/// assert_eq!( workload(100, 42), workload(100, 42) );
+/// assert_ne!( workload(100, 42), workload(100, 43) );
+/// ```
+pub fn workload(len: usize, seed: u64) -> Vec<u64>
+{
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            state = state.wrapping_add(0x9e3779b97f4a7c15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            z ^ (z >> 31)
+        })
+        .collect()
+}
+
+/// Generates a workload of increasing integers.
+///
+/// When selecting the greatest items, this is the worst case:
+/// once the top set is full, each insertion evicts the lowest item.
+pub fn ascending_workload(len: usize) -> Vec<u64>
+{
+    (0..len as u64).collect()
+}
+
+/// Counts the challenges performed to insert a sequence of items.
+///
+/// A top set of capacity `n` is built with the challenge `beat`
+/// and all the `items` are inserted in order.
+///
+/// # Example
+/// ```
+/// # use topset::bench::{ascending_workload, comparisons_for_workload};
+/// // each insertion in a full top set costs a challenge against the lowest item
+/// let count = comparisons_for_workload(1, ascending_workload(10), u64::gt);
+/// assert_eq!( count, 9 );
+/// ```
+pub fn comparisons_for_workload<X,I,C>(n: usize, items: I, beat: C) -> usize
+    where I: IntoIterator<Item=X>, C: Fn(&X,&X) -> bool
+{
+    let count = Cell::new(0);
+    let mut top = TopSet::new(n, |a: &X, b: &X| { count.set(count.get() + 1); beat(a,b) });
+    top.extend(items);
+    drop(top);
+    count.get()
+}
+
+/// Moves the item at index `i` of the heap up to its place.
+///
+/// This is the internal sift of [`TopSet::insert`], exposed for benchmarking.
+pub fn percolate_up<X,C>(top: &mut TopSet<X,C>, i: usize)
+    where C: Fn(&X,&X) -> bool
+{
+    top.percolate_up(i)
+}
+
+/// Moves the item at index `i` of the heap down to its place.
+///
+/// This is the internal sift of [`TopSet::pop`], exposed for benchmarking.
+pub fn percolate_down<X,C>(top: &mut TopSet<X,C>, i: usize)
+    where C: Fn(&X,&X) -> bool
+{
+    top.percolate_down(i)
+}
@@ -0,0 +1,209 @@
+use alloc::vec::Vec;
+use core::iter::{self, Empty};
+use crate::{EvictingTopSet, TopSet};
+use crate::heap::Ties;
+
+/// How the ties (items which do not beat each other) are broken.
+///
+/// See [`TopSetBuilder::tie_break`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TieBreak
+{
+    /// The ties are not broken (as with [`TopSet::new`]).
+    #[default]
+    Unbroken,
+    /// The item inserted first wins (as with [`TopSet::new_deterministic`]).
+    FirstInserted,
+    /// The item inserted last wins (as with [`TopSet::new_fifo`]).
+    LastInserted
+}
+
+impl From<TieBreak> for Ties
+{
+    fn from(policy: TieBreak) -> Self
+    {
+        match policy {
+            TieBreak::Unbroken => Ties::Unbroken,
+            TieBreak::FirstInserted => Ties::Oldest,
+            TieBreak::LastInserted => Ties::Newest
+        }
+    }
+}
+
+/// The absence of an eviction hook in a [`TopSetBuilder`].
+#[derive(Clone, Copy, Debug)]
+pub struct NoHook;
+
+/// A builder to configure a top set step by step.
+///
+/// It is created by [`TopSet::builder`]. A challenge should be given (by
+/// [`Self::comparator`]) before calling [`Self::build`]: otherwise, the
+/// method `build` does not exist and the code does not compile.
+///
+/// By default, the capacity is unlimited (all the items are kept), there is
+/// no initial item, the ties are not broken and there is no eviction hook.
+///
+/// # Example
+/// ```
+/// # use topset::{TieBreak, TopSet};
+/// let topset = TopSet::builder()
+///     .capacity(2)
+///     .comparator(|a: &(u32,char), b: &(u32,char)| a.0 > b.0)
+///     .tie_break(TieBreak::FirstInserted)
+///     .init(vec![(1,'a'), (3,'b'), (3,'c'), (3,'d')])
+///     .build();
+/// assert_eq!( topset.into_sorted_vec(), vec![(3,'c'), (3,'b')]);
+/// ```
+#[must_use]
+pub struct TopSetBuilder<X,C,I,H>
+{
+    count: usize,
+    beat: C,
+    init: I,
+    ties: TieBreak,
+    on_evict: H,
+    items: core::marker::PhantomData<X>
+}
+
+impl<X> TopSetBuilder<X,(),Empty<X>,NoHook>
+{
+    // internal stuff
+    // the default settings (see `TopSet::builder`)
+    pub(crate) fn new() -> Self
+    {
+        TopSetBuilder {
+            count: usize::MAX,
+            beat: (),
+            init: iter::empty(),
+            ties: TieBreak::Unbroken,
+            on_evict: NoHook,
+            items: core::marker::PhantomData
+        }
+    }
+}
+
+impl<X,C,I,H> TopSetBuilder<X,C,I,H>
+{
+    /// Sets the maximum number of stored items.
+    pub fn capacity(self, n: usize) -> Self
+    {
+        Self { count: n, ..self }
+    }
+
+    /// Sets the challenge (see [`TopSet::new`]).
+    pub fn comparator<C2>(self, beat: C2) -> TopSetBuilder<X,C2,I,H>
+        where C2: Fn(&X,&X) -> bool
+    {
+        let Self { count, init, ties, on_evict, items, .. } = self;
+        TopSetBuilder { count, beat, init, ties, on_evict, items }
+    }
+
+    /// Sets the initial items (see [`TopSet::with_init`]).
+    pub fn init<I2>(self, init: I2) -> TopSetBuilder<X,C,I2::IntoIter,H>
+        where I2: IntoIterator<Item=X>
+    {
+        let Self { count, beat, ties, on_evict, items, .. } = self;
+        TopSetBuilder { count, beat, init: init.into_iter(), ties, on_evict, items }
+    }
+
+    /// Sets how the ties are broken.
+    pub fn tie_break(self, policy: TieBreak) -> Self
+    {
+        Self { ties: policy, ..self }
+    }
+
+    /// Sets a hook called with each removed item (see [`TopSet::with_evict_hook`]).
+    ///
+    /// The built top set is then an [`EvictingTopSet`]. The initial items which
+    /// are not kept are also given to the hook.
+    pub fn on_evict<H2>(self, on_evict: H2) -> TopSetBuilder<X,C,I,H2>
+        where H2: FnMut(X)
+    {
+        let Self { count, beat, init, ties, items, .. } = self;
+        TopSetBuilder { count, beat, init, ties, on_evict, items }
+    }
+}
+
+impl<X,C,I> TopSetBuilder<X,C,I,NoHook>
+    where C: Fn(&X,&X) -> bool, I: Iterator<Item=X>
+{
+    /// Builds the configured top set.
+    pub fn build(self) -> TopSet<X,C>
+    {
+        let mut top = empty(self.count, self.beat, self.ties, &self.init);
+        top.refill(self.init);
+        top
+    }
+}
+
+impl<X,C,I,H> TopSetBuilder<X,C,I,H>
+    where C: Fn(&X,&X) -> bool, I: Iterator<Item=X>, H: FnMut(X)
+{
+    /// Builds the configured top set with its eviction hook.
+    pub fn build(self) -> EvictingTopSet<X,C,H>
+    {
+        let mut top = empty(self.count, self.beat, self.ties, &self.init).with_evict_hook(self.on_evict);
+        top.extend(self.init);
+        top
+    }
+}
+
+// internal stuff
+// an empty top set whose storage is allocated for the expected number of items
+fn empty<X,C,I>(count: usize, beat: C, ties: TieBreak, init: &I) -> TopSet<X,C>
+    where C: Fn(&X,&X) -> bool, I: Iterator<Item=X>
+{
+    // the capacity could be unlimited, so only the known items are allocated
+    let n = count.min(init.size_hint().0);
+    let mut top = TopSet::with_storage(count, beat, Vec::with_capacity(n));
+    top.ties = ties.into();
+    if top.ties != Ties::Unbroken {
+        top.stamps.reserve(n);
+    }
+    top
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use crate::{TieBreak, TopSet};
+
+    #[test]
+    fn configured_step_by_step()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+
+        let top = TopSet::builder().capacity(3).comparator(i32::gt).init(items.clone()).build();
+        assert_eq![ top, TopSet::with_init(3, i32::gt, items.clone()) ];
+
+        // unlimited by default
+        let top = TopSet::builder().comparator(i32::lt).init(items.clone()).build();
+        assert_eq![ top.capacity(), usize::MAX ];
+        assert_eq![ top.len(), items.len() ];
+        assert_eq![ TopSet::builder().capacity(2).comparator(i32::gt).build().len(), 0 ];
+
+        // the same as the dedicated constructors
+        let tagged = vec![(1,'a'), (3,'b'), (3,'c'), (3,'d')];
+        let beat = |a: &(u32,char), b: &(u32,char)| a.0 > b.0;
+        for (policy, expected) in [(TieBreak::FirstInserted, TopSet::new_deterministic(2, beat)), (TieBreak::LastInserted, TopSet::new_fifo(2, beat))] {
+            let mut expected = expected;
+            expected.extend(tagged.clone());
+            let top = TopSet::builder().comparator(beat).tie_break(policy).capacity(2).init(tagged.clone()).build();
+            assert_eq![ top.into_sorted_vec(), expected.into_sorted_vec() ];
+        }
+
+        // with an eviction hook, the rejected initial items are released too
+        let released = RefCell::new(vec![]);
+        let mut top = TopSet::builder()
+            .on_evict(|x| released.borrow_mut().push(x))
+            .init(items.clone())
+            .comparator(i32::gt)
+            .capacity(3)
+            .build();
+        assert_eq![ released.borrow().len(), items.len() - 3 ];
+        top.insert(1000);
+        assert_eq![ released.borrow().last(), Some(&81) ];
+        assert_eq![ top.into_inner().into_sorted_vec(), vec![97, 877, 1000] ];
+    }
+}
@@ -0,0 +1,109 @@
+use core::ops::{Deref, DerefMut};
+use crate::TopSet;
+
+/// The top set of the `N` greatest items, built by [`Iterator::collect`].
+///
+/// The capacity is given by the const parameter `N` and the challenge
+/// is [`PartialOrd::gt`]. It dereferences to the inner [`TopSet`]
+/// and could be converted into it with [`From`] (or `into()`),
+/// which is needed to call the consuming methods (e.g. [`TopSet::into_sorted_vec`]).
+///
+/// # Example
+/// ```
+/// # use topset::{Greatest, TopSet};
+/// let top: Greatest<i32, 3> = vec![7,5,6,9,4,2,3].into_iter().collect();
+/// assert_eq!( top.peek(), Some(&6) );
+/// let top: TopSet<_,_> = top.into();
+/// assert_eq!( top.into_sorted_vec(), vec![6,7,9]);
+/// ```
+#[derive(Clone)]
+pub struct Greatest<T, const N: usize>(TopSet<T, fn(&T,&T) -> bool>);
+
+/// The top set of the `N` lowest items, built by [`Iterator::collect`].
+///
+/// The capacity is given by the const parameter `N` and the challenge
+/// is [`PartialOrd::lt`]. It dereferences to the inner [`TopSet`]
+/// and could be converted into it with [`From`] (or `into()`),
+/// which is needed to call the consuming methods (e.g. [`TopSet::into_sorted_vec`]).
+///
+/// # Example
+/// ```
+/// # use topset::Lowest;
+/// let top: Lowest<i32, 3> = vec![7,5,6,9,4,2,3].into_iter().collect();
+/// assert_eq!( top.peek(), Some(&4) );
+/// ```
+#[derive(Clone)]
+pub struct Lowest<T, const N: usize>(TopSet<T, fn(&T,&T) -> bool>);
+
+impl<T: PartialOrd, const N: usize> FromIterator<T> for Greatest<T,N>
+{
+    fn from_iter<I: IntoIterator<Item=T>>(iter: I) -> Self
+    {
+        Self(TopSet::with_init(N, T::gt, iter))
+    }
+}
+
+impl<T: PartialOrd, const N: usize> FromIterator<T> for Lowest<T,N>
+{
+    fn from_iter<I: IntoIterator<Item=T>>(iter: I) -> Self
+    {
+        Self(TopSet::with_init(N, T::lt, iter))
+    }
+}
+
+impl<T, const N: usize> Deref for Greatest<T,N>
+{
+    type Target = TopSet<T, fn(&T,&T) -> bool>;
+    #[inline] fn deref(&self) -> &Self::Target { &self.0 }
+}
+
+impl<T, const N: usize> DerefMut for Greatest<T,N>
+{
+    #[inline] fn deref_mut(&mut self) -> &mut Self::Target { &mut self.0 }
+}
+
+impl<T, const N: usize> Deref for Lowest<T,N>
+{
+    type Target = TopSet<T, fn(&T,&T) -> bool>;
+    #[inline] fn deref(&self) -> &Self::Target { &self.0 }
+}
+
+impl<T, const N: usize> DerefMut for Lowest<T,N>
+{
+    #[inline] fn deref_mut(&mut self) -> &mut Self::Target { &mut self.0 }
+}
+
+impl<T, const N: usize> From<Greatest<T,N>> for TopSet<T, fn(&T,&T) -> bool>
+{
+    #[inline] fn from(top: Greatest<T,N>) -> Self { top.0 }
+}
+
+impl<T, const N: usize> From<Lowest<T,N>> for TopSet<T, fn(&T,&T) -> bool>
+{
+    #[inline] fn from(top: Lowest<T,N>) -> Self { top.0 }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{Greatest, Lowest, TopSet};
+
+    #[test]
+    fn collected()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+
+        let top: Greatest<u32, 5> = items.iter().copied().collect();
+        assert_eq![ top.capacity(), 5 ];
+        assert_eq![ TopSet::from(top).into_sorted_vec(), vec![22, 45, 81, 97, 877] ];
+
+        let mut top: Lowest<u32, 3> = items.into_iter().collect();
+        assert_eq![ top.insert(3), Some(3) ];
+        assert_eq![ top.insert(0), Some(1) ];
+        assert_eq![ TopSet::from(top.clone()).into_sorted_vec(), vec![1, 0, 0] ];
+
+        let empty: Greatest<u32, 4> = std::iter::empty().collect();
+        assert!( empty.is_empty() );
+        assert_eq![ empty.capacity(), 4 ];
+    }
+}
@@ -0,0 +1,197 @@
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+use core::mem;
+use crate::heap::HeapBuffer;
+
+/// A top N set of items stored in a `D`-ary heap.
+///
+/// It behaves as [`crate::TopSet`] but each node of the heap has `D` children
+/// (instead of 2). The heap is shallower, so an insertion climbs fewer levels
+/// and the children of a node are contiguous in memory, which is more cache friendly
+/// for large capacities. On the other hand, sifting an item down challenges all the
+/// `D` children at each level.
+///
+/// The arity `D` should be at least 2 (this is checked at compile time).
+/// See the example `arity` for a comparison of the running times.
+///
+/// # Example
+/// ```
+/// # use topset::DaryTopSet;
+/// let mut topset = DaryTopSet::<u32,_,4>::new(2, u32::gt);
+/// topset.extend(vec![7,5,6,9,4,2,3]);
+/// assert_eq!( topset.into_sorted_vec(), vec![7,9]);
+/// ```
+#[derive(Clone)]
+pub struct DaryTopSet<X,C,const D: usize>
+    where C: Fn(&X,&X) -> bool
+{
+    heap: Vec<X>, // a heap with the greatest at the end
+    count: usize,
+    beat:  C
+}
+
+impl<X,C,const D: usize> DaryTopSet<X,C,D>
+    where C: Fn(&X,&X) -> bool
+{
+    /// Creates a new `D`-ary top set with a selecting closure.
+    ///
+    /// See [`crate::TopSet::new`] for the meaning of the parameters.
+    pub fn new(n: usize, beat: C) -> Self
+    {
+        Self {
+            heap: Vec::with_capacity(n),
+            count: n,
+            beat
+        }
+    }
+
+    /// Creates a new `D`-ary top set with a selecting closure and an initial set of items.
+    ///
+    /// See [`crate::TopSet::with_init`].
+    pub fn with_init<I: IntoIterator<Item=X>>(n: usize, beat: C, init: I) -> Self
+    {
+        let mut top = Self::new(n, beat);
+        top.extend(init);
+        top
+    }
+
+    /// Check if the top set is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.heap.is_empty() }
+
+    /// Get the number of stored items.
+    #[inline]
+    pub fn len(&self) -> usize { self.heap.len() }
+
+    /// Get the capacity of this top set
+    #[inline]
+    pub fn capacity(&self) -> usize { self.count }
+
+    /// Read access to the lowest item of the top set
+    #[inline]
+    pub fn peek(&self) -> Option<&X>
+    {
+        self.heap.first()
+    }
+
+    /// Checks if an item will be inserted or not
+    #[inline]
+    pub fn is_candidate(&self, x: &X) -> bool {
+        self.heap.len() < self.count || self.peek().is_some_and(|p| (self.beat)(x, p))
+    }
+
+    /// Iterate over all the top selected items (**not** sorted).
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item=&X>
+    {
+        self.heap.iter()
+    }
+
+    /// Gets all the top set elements in a vector (**not** sorted).
+    #[inline]
+    pub fn into_vec(self) -> Vec<X> { self.heap }
+
+    /// Insert a new item.
+    ///
+    /// See [`crate::TopSet::insert`].
+    pub fn insert(&mut self, mut x: X) -> Option<X>
+    {
+        if self.heap.len() < self.count {
+            // some room left, so nothing to remove
+            self.heap.push(x);
+            self.percolate_up(self.heap.len()-1);
+            None
+        } else {
+            if self.count != 0 && (self.beat)(&x, &self.heap[0]) {
+                // put the greatest the deepest: the new one should be kept
+                mem::swap(&mut x, &mut self.heap[0]);
+                self.percolate_down(0);
+            }
+            Some(x)
+        }
+    }
+
+    /// Pop the lowest item of the top set
+    pub fn pop(&mut self) -> Option<X>
+    {
+        if self.heap.is_empty() {
+            None
+        } else {
+            let pop = self.heap.swap_remove(0);
+            self.percolate_down(0);
+            Some(pop)
+        }
+    }
+
+    /// Returns the topset in a sorted vector.
+    ///
+    /// The first element of the vector is the _lowest_ item of the top set
+    /// and the last one is the _greatest_ one.
+    pub fn into_sorted_vec(mut self) -> Vec<X>
+    {
+        let mut sorted = Vec::with_capacity(self.heap.len());
+        while let Some(x) = self.pop() {
+            sorted.push(x);
+        }
+        sorted
+    }
+
+    /// Removes all the elements in the top set
+    #[inline] pub fn clear(&mut self) { self.heap.clear() }
+}
+
+impl<X,C,const D: usize> HeapBuffer for DaryTopSet<X,C,D>
+    where C: Fn(&X,&X) -> bool
+{
+    const ARITY: usize = {
+        assert!(D >= 2, "the arity of a heap should be at least 2");
+        D
+    };
+    #[inline] fn size(&self) -> usize { self.heap.len() }
+    #[inline] fn duel(&self, i: usize, j: usize) -> bool { (self.beat)(&self.heap[i], &self.heap[j]) }
+    #[inline] fn exchange(&mut self, i: usize, j: usize) { self.heap.swap(i, j) }
+}
+
+impl<X,C,const D: usize> Extend<X> for DaryTopSet<X,C,D>
+    where C: Fn(&X,&X) -> bool
+{
+    #[inline]
+    fn extend<T: IntoIterator<Item=X>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|x| { self.insert(x); } )
+    }
+}
+
+impl<X,C,const D: usize> Debug for DaryTopSet<X,C,D>
+    where X:Debug, C: Fn(&X,&X) -> bool
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.heap.fmt(f)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use crate::{DaryTopSet, TopSet};
+
+    #[test]
+    fn same_as_binary()
+    {
+        let mut state = 7u64;
+        let items = (0..3000).map(|_| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 33) % 500
+        }).collect::<Vec<_>>();
+
+        for n in [0, 1, 2, 3, 5, 17, 100, 3000] {
+            let expected = TopSet::with_init(n, u64::gt, items.iter().copied()).into_sorted_vec();
+            assert_eq![ DaryTopSet::<u64,_,2>::with_init(n, u64::gt, items.iter().copied()).into_sorted_vec(), expected ];
+            assert_eq![ DaryTopSet::<u64,_,3>::with_init(n, u64::gt, items.iter().copied()).into_sorted_vec(), expected ];
+            let mut quaternary = DaryTopSet::<u64,_,4>::with_init(n, u64::gt, items.iter().copied());
+            assert_eq![ quaternary.peek(), expected.first() ];
+            assert_eq![ quaternary.insert(1000), if n == 0 { Some(1000) } else { expected.first().copied() } ];
+            assert_eq![ quaternary.into_sorted_vec().last(), if n == 0 { None } else { Some(&1000) } ];
+        }
+    }
+}
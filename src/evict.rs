@@ -0,0 +1,125 @@
+use core::ops::Deref;
+use crate::TopSet;
+
+/// A top set which hands the removed items over to a hook.
+///
+/// Each item which does not make it into the top set (a stored item evicted
+/// by a better one, or a new item which is rejected) is given by value to the
+/// hook `on_evict` before being dropped. This lets the items release their
+/// resources even when they are inserted through [`Extend::extend`].
+///
+/// It is built by [`TopSet::with_evict_hook`] and dereferences to the inner
+/// [`TopSet`] for a read-only access. The items returned by [`TopSet::pop`]
+/// (after [`Self::into_inner`]) are given to the caller, not to the hook.
+///
+/// # Example
+/// ```
+/// # use topset::TopSet;
+/// let mut released = vec![];
+/// let mut topset = TopSet::new(2, u32::gt).with_evict_hook(|x| released.push(x));
+/// topset.extend(vec![7,5,6,9,4]);
+/// topset.resize(1);
+/// assert_eq!( topset.into_inner().into_sorted_vec(), vec![9]);
+/// assert_eq!( released, vec![5,6,4,7]);
+/// ```
+pub struct EvictingTopSet<X,C,H>
+    where C: Fn(&X,&X) -> bool, H: FnMut(X)
+{
+    top: TopSet<X,C>,
+    on_evict: H
+}
+
+impl<X,C> TopSet<X,C>
+    where C: Fn(&X,&X) -> bool
+{
+    /// Registers a hook called with each item removed by an insertion or a resize.
+    ///
+    /// See [`EvictingTopSet`].
+    pub fn with_evict_hook<H: FnMut(X)>(self, on_evict: H) -> EvictingTopSet<X,C,H>
+    {
+        EvictingTopSet { top: self, on_evict }
+    }
+}
+
+impl<X,C,H> EvictingTopSet<X,C,H>
+    where C: Fn(&X,&X) -> bool, H: FnMut(X)
+{
+    /// Insert a new item.
+    ///
+    /// The removed item, if any, is given to the hook (see [`TopSet::insert`]).
+    /// Returns `true` if the new item is stored and `false` if it was rejected.
+    pub fn insert(&mut self, x: X) -> bool
+    {
+        match self.top.insert_changed(x) {
+            (Some(removed), changed) => { (self.on_evict)(removed); changed }
+            (None, changed) => changed
+        }
+    }
+
+    /// Resize the top set
+    ///
+    /// If the size decreases, the lowest items are removed and given to the hook
+    /// (see [`TopSet::resize`]).
+    pub fn resize(&mut self, n: usize)
+    {
+        while self.top.len() > n {
+            if let Some(x) = self.top.pop() {
+                (self.on_evict)(x);
+            }
+        }
+        self.top.resize(n);
+    }
+
+    /// Gets the inner top set, dropping the hook.
+    #[inline]
+    pub fn into_inner(self) -> TopSet<X,C> { self.top }
+}
+
+impl<X,C,H> Deref for EvictingTopSet<X,C,H>
+    where C: Fn(&X,&X) -> bool, H: FnMut(X)
+{
+    type Target = TopSet<X,C>;
+    #[inline] fn deref(&self) -> &Self::Target { &self.top }
+}
+
+impl<X,C,H> Extend<X> for EvictingTopSet<X,C,H>
+    where C: Fn(&X,&X) -> bool, H: FnMut(X)
+{
+    #[inline]
+    fn extend<T: IntoIterator<Item=X>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|x| { self.insert(x); } )
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use crate::TopSet;
+
+    #[test]
+    fn released_resources()
+    {
+        // every item is either kept or released, exactly once
+        let released = RefCell::new(vec![]);
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        let mut top = TopSet::new(5, i32::gt).with_evict_hook(|x| released.borrow_mut().push(x));
+        assert!( top.insert(3) );
+        assert!( top.insert(-1) );
+        top.extend(items.clone());
+        assert_eq![ top.len(), 5 ];
+        top.resize(3);
+        assert_eq![ top.capacity(), 3 ];
+        let kept = top.into_inner().into_sorted_vec();
+        assert_eq![ kept, vec![81, 97, 877] ];
+
+        let mut all = released.take();
+        assert_eq![ all.len(), items.len() + 2 - kept.len() ];
+        all.extend(kept);
+        all.sort();
+        let mut expected = items;
+        expected.extend([3, -1]);
+        expected.sort();
+        assert_eq![ all, expected ];
+    }
+}
@@ -0,0 +1,254 @@
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+use core::mem;
+
+/// A top N set of items with a fallible challenge.
+///
+/// It behaves as [`crate::TopSet`] but the closure `beat` returns a `Result`:
+/// when a duel fails, the error is propagated and the top set is left unchanged.
+/// To achieve this, each operation first challenges the items without moving any
+/// of them, and then moves them only when all the duels succeeded.
+///
+/// # Example
+/// ```
+/// # use topset::TryTopSet;
+/// // the items are parsed at each duel
+/// let mut topset = TryTopSet::new(2, |a: &&str, b: &&str| Ok::<_,std::num::ParseIntError>(a.parse::<u32>()? > b.parse::<u32>()?));
+/// assert_eq!( topset.try_insert("7"), Ok(None) );
+/// assert_eq!( topset.try_insert("12"), Ok(None) );
+/// assert_eq!( topset.try_insert("9"), Ok(Some("7")) );
+/// assert!( topset.try_insert("x").is_err() );
+/// assert_eq!( topset.try_into_sorted_vec(), Ok(vec!["9","12"]) );
+/// ```
+#[derive(Clone)]
+pub struct TryTopSet<X,C>
+{
+    heap: Vec<X>, // a heap with the greatest at the end
+    count: usize,
+    beat: C
+}
+
+impl<X,C,E> TryTopSet<X,C>
+    where C: Fn(&X,&X) -> Result<bool,E>
+{
+    /// Creates a new top set with a fallible selecting closure.
+    ///
+    /// See [`crate::TopSet::new`] for the meaning of the parameters.
+    pub fn new(n: usize, beat: C) -> Self
+    {
+        Self {
+            heap: Vec::with_capacity(n),
+            count: n,
+            beat
+        }
+    }
+
+    /// Check if the top set is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.heap.is_empty() }
+
+    /// Get the number of stored items.
+    #[inline]
+    pub fn len(&self) -> usize { self.heap.len() }
+
+    /// Get the capacity of this top set
+    #[inline]
+    pub fn capacity(&self) -> usize { self.count }
+
+    /// Read access to the lowest item of the top set
+    #[inline]
+    pub fn peek(&self) -> Option<&X> { self.heap.first() }
+
+    /// Iterate over all the top selected items (**not** sorted).
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item=&X> { self.heap.iter() }
+
+    /// Gets all the top set elements in a vector (**not** sorted).
+    #[inline]
+    pub fn into_vec(self) -> Vec<X> { self.heap }
+
+    /// Insert a new item.
+    ///
+    /// On success, the result is the same as for [`crate::TopSet::insert`].
+    /// If a duel fails, the error is returned, the new item is dropped
+    /// and the top set is unchanged.
+    pub fn try_insert(&mut self, x: X) -> Result<Option<X>,E>
+    {
+        if self.heap.len() < self.count {
+            // some room left: find the place of x on the path to the root
+            let mut pos = self.heap.len();
+            while pos > 0 {
+                let parent = (pos-1)/2;
+                if (self.beat)(&self.heap[parent], &x)? {
+                    pos = parent;
+                } else {
+                    break;
+                }
+            }
+            self.heap.push(x);
+            let mut i = self.heap.len()-1;
+            while i > pos {
+                self.heap.swap(i, (i-1)/2);
+                i = (i-1)/2;
+            }
+            Ok(None)
+        } else if self.count != 0 && (self.beat)(&x, &self.heap[0])? {
+            let path = self.path_down(&x, self.heap.len())?;
+            let removed = mem::replace(&mut self.heap[0], x);
+            self.move_down(&path);
+            Ok(Some(removed))
+        } else {
+            Ok(Some(x))
+        }
+    }
+
+    /// Inserts all the items of an iterator.
+    ///
+    /// The insertions stop at the first failing duel and its error is returned
+    /// (the items already inserted are kept, the remaining ones are not consumed).
+    pub fn try_extend<I: IntoIterator<Item=X>>(&mut self, iter: I) -> Result<(),E>
+    {
+        iter.into_iter().try_for_each(|x| self.try_insert(x).map(drop))
+    }
+
+    /// Pop the lowest item of the top set
+    ///
+    /// If a duel fails, the error is returned and the top set is unchanged.
+    pub fn try_pop(&mut self) -> Result<Option<X>,E>
+    {
+        match self.heap.len() {
+            0 => Ok(None),
+            len => {
+                // the last item replaces the root and goes down among the others
+                let path = self.path_down(&self.heap[len-1], len-1)?;
+                let pop = self.heap.swap_remove(0);
+                self.move_down(&path);
+                Ok(Some(pop))
+            }
+        }
+    }
+
+    /// Returns the topset in a sorted vector.
+    ///
+    /// The first element of the vector is the _lowest_ item of the top set
+    /// and the last one is the _greatest_ one. If a duel fails, the error
+    /// is returned and the items are dropped.
+    pub fn try_into_sorted_vec(mut self) -> Result<Vec<X>,E>
+    {
+        let mut sorted = Vec::with_capacity(self.heap.len());
+        while let Some(x) = self.try_pop()? {
+            sorted.push(x);
+        }
+        Ok(sorted)
+    }
+
+    /// Removes all the elements in the top set
+    #[inline] pub fn clear(&mut self) { self.heap.clear() }
+
+    // internal stuff
+    // the positions crossed by an item going down from the root
+    // (among the `size` first items, the root being ignored)
+    fn path_down(&self, x: &X, size: usize) -> Result<Vec<usize>,E>
+    {
+        let mut path = Vec::new();
+        let mut pos = 0;
+        loop {
+            let mut child = 2*pos+1;
+            if child >= size {
+                break;
+            }
+            // to put the greatest the deepest -> select the lowest child
+            if child+1 < size && (self.beat)(&self.heap[child], &self.heap[child+1])? {
+                child += 1;
+            }
+            if (self.beat)(x, &self.heap[child])? {
+                path.push(child);
+                pos = child;
+            } else {
+                break;
+            }
+        }
+        Ok(path)
+    }
+
+    // internal stuff
+    // moves the root along a path computed by `path_down`
+    fn move_down(&mut self, path: &[usize])
+    {
+        let mut pos = 0;
+        for &child in path {
+            self.heap.swap(pos, child);
+            pos = child;
+        }
+    }
+}
+
+impl<X,C> Debug for TryTopSet<X,C>
+    where X: Debug
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.heap.fmt(f)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use crate::{TopSet, TryTopSet};
+
+    #[test]
+    fn same_as_infallible()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        for n in [0, 1, 2, 5, 15, 20] {
+            let mut top = TryTopSet::new(n, |a: &i32, b: &i32| Ok::<_,()>(a > b));
+            let mut expected = TopSet::new(n, i32::gt);
+            for x in items.iter().copied() {
+                assert_eq![ top.try_insert(x), Ok(expected.insert(x)) ];
+                assert_eq![ top.peek(), expected.peek() ];
+            }
+            assert_eq![ top.try_into_sorted_vec(), Ok(expected.into_sorted_vec()) ];
+        }
+    }
+
+    #[test]
+    fn untouched_on_error()
+    {
+        // the duels fail once a budget is exhausted
+        let budget = Cell::new(usize::MAX);
+        let beat = |a: &i32, b: &i32| {
+            match budget.get() {
+                0 => Err("exhausted"),
+                left => { budget.set(left-1); Ok(a > b) }
+            }
+        };
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+
+        for n in [4, 10, 20] {
+            let mut top = TryTopSet::new(n, beat);
+            top.try_extend(items.iter().copied()).unwrap();
+            let before = top.clone().into_vec();
+
+            // whatever the failing duel, nothing is moved
+            for left in 0..4 {
+                for x in [1000, 50, 3] {
+                    let mut attempt = top.clone();
+                    budget.set(left);
+                    if attempt.try_insert(x).is_err() {
+                        assert_eq![ attempt.into_vec(), before ];
+                    }
+                }
+                let mut attempt = top.clone();
+                budget.set(left);
+                if attempt.try_pop().is_err() {
+                    assert_eq![ attempt.into_vec(), before ];
+                }
+            }
+            budget.set(0);
+            assert_eq![ top.try_extend(vec![2000, 3000]), Err("exhausted") ];
+            assert_eq![ top.into_vec(), before ];
+            budget.set(usize::MAX);
+        }
+    }
+}
@@ -0,0 +1,116 @@
+/// A challenge selecting the greatest floats, with a total order (including `NaN`).
+///
+/// It relies on [`f64::total_cmp`]: a positive `NaN` is greater than any
+/// other float (even the infinity) and a negative `NaN` is lower than any other float.
+/// So, the positive `NaN`s are always selected first.
+///
+/// # Example
+/// ```
+/// # use topset::TopSetReducing;
+/// let top = vec![1.5, f64::NAN, -3., 8.].topset(2, topset::greatest_f64());
+/// let top = top.into_sorted_vec();
+/// assert_eq!( top[0], 8. );
+/// assert!( top[1].is_nan() );
+/// ```
+#[inline]
+pub fn greatest_f64() -> fn(&f64,&f64) -> bool { |a,b| a.total_cmp(b).is_gt() }
+
+/// A challenge selecting the lowest floats, with a total order (including `NaN`).
+///
+/// See [`greatest_f64`]: the positive `NaN`s are always selected last.
+///
+/// # Example
+/// ```
+/// # use topset::TopSetReducing;
+/// let top = vec![1.5, f64::NAN, -3., 8.].topset(2, topset::lowest_f64());
+/// assert_eq!( top.into_sorted_vec(), vec![1.5, -3.] );
+/// ```
+#[inline]
+pub fn lowest_f64() -> fn(&f64,&f64) -> bool { |a,b| a.total_cmp(b).is_lt() }
+
+/// A challenge selecting the greatest floats, with a total order (including `NaN`).
+///
+/// See [`greatest_f64`].
+#[inline]
+pub fn greatest_f32() -> fn(&f32,&f32) -> bool { |a,b| a.total_cmp(b).is_gt() }
+
+/// A challenge selecting the lowest floats, with a total order (including `NaN`).
+///
+/// See [`greatest_f64`].
+#[inline]
+pub fn lowest_f32() -> fn(&f32,&f32) -> bool { |a,b| a.total_cmp(b).is_lt() }
+
+
+/// The float types for which a `NaN` breaks the [`PartialOrd`] challenges.
+///
+/// This trait is sealed: it is only implemented for [`f32`] and [`f64`].
+/// See [`crate::TopSet::new_float`].
+pub trait MaybeNan: sealed::Sealed
+{
+    /// Checks if the value is a `NaN`.
+    fn is_nan_value(&self) -> bool;
+}
+
+impl MaybeNan for f32 { #[inline] fn is_nan_value(&self) -> bool { self.is_nan() } }
+impl MaybeNan for f64 { #[inline] fn is_nan_value(&self) -> bool { self.is_nan() } }
+
+mod sealed {
+    pub trait Sealed {}
+    impl Sealed for f32 {}
+    impl Sealed for f64 {}
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::float::MaybeNan;
+    use crate::iter::TopSetReducing;
+    use crate::{greatest_f32, lowest_f64, TopSet};
+
+    #[test]
+    fn total_challenges()
+    {
+        let items = vec![1., f64::NAN, f64::INFINITY, -f64::NAN, f64::NEG_INFINITY, 0., -0.];
+        let lowest = items.clone().topset(3, lowest_f64()).into_sorted_vec();
+        assert_eq![ lowest[1], f64::NEG_INFINITY ];
+        assert!( lowest[0] == 0. && lowest[0].is_sign_negative() );
+        assert!( lowest[2].is_nan() && lowest[2].is_sign_negative() );
+
+        let greatest = items.into_iter().map(|x| x as f32).topset(2, greatest_f32()).into_sorted_vec();
+        assert_eq![ greatest[0], f32::INFINITY ];
+        assert!( greatest[1].is_nan() && greatest[1].is_sign_positive() );
+    }
+
+    #[test]
+    fn nan_detection()
+    {
+        assert!( f64::NAN.is_nan_value() );
+        assert!( f32::NAN.is_nan_value() );
+        assert!( !1.5f64.is_nan_value() );
+        assert!( !f32::INFINITY.is_nan_value() );
+
+        let mut top = TopSet::new_float(3, f32::lt);
+        top.extend(vec![1., 4., -2., 3.]);
+        assert_eq![ top.into_sorted_vec(), vec![3., 1., -2.] ];
+    }
+
+    #[test]
+    fn total_order_accepts_nan()
+    {
+        let mut top = TopSet::new_total_f32(3);
+        top.extend(vec![1., f32::NAN, 4., -2., 3.]);
+        assert_eq![ top.pop(), Some(3.) ];
+        assert_eq![ top.pop(), Some(4.) ];
+        assert!( top.pop().unwrap().is_nan() );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "new_total_f64")]
+    fn nan_with_partial_order()
+    {
+        let mut top = TopSet::new_float(3, f64::gt);
+        top.extend(vec![1., 4., -2.]);
+        top.insert(f64::NAN);
+    }
+}
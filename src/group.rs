@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use crate::TopSet;
+
+/// Selects the top `n` items of each group of an iterator.
+///
+/// Each item is inserted into the top set of its group, given by `key`.
+/// The capacity `n` applies to each group and the items are only challenged
+/// (by `beat`) against the items of the same group.
+///
+/// # Example
+/// ```
+/// # use topset::group_topset;
+/// // (endpoint, duration in ms)
+/// let logs = vec![("/a", 12), ("/b", 340), ("/a", 95), ("/b", 8), ("/a", 40), ("/c", 1)];
+/// let slowest = group_topset(logs, 2, |log| log.0, |a, b| a.1 > b.1);
+/// assert_eq!( slowest.len(), 3 );
+/// assert_eq!( slowest["/a"].clone().into_sorted_vec(), vec![("/a",40), ("/a",95)] );
+/// assert_eq!( slowest["/c"].len(), 1 );
+/// ```
+pub fn group_topset<I, K, V, KF, C>(iter: I, n: usize, key: KF, beat: C) -> HashMap<K, TopSet<V,C>>
+    where I: IntoIterator<Item=V>, K: Hash + Eq, KF: Fn(&V) -> K, C: Fn(&V,&V) -> bool + Clone
+{
+    let mut groups = HashMap::new();
+    for x in iter {
+        groups.entry(key(&x))
+            .or_insert_with(|| TopSet::new(n, beat.clone()))
+            .insert(x);
+    }
+    groups
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::group_topset;
+
+    #[test]
+    fn per_group()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        let groups = group_topset(items.clone(), 2, |x| x % 2, i32::gt);
+        assert_eq![ groups.len(), 2 ];
+        assert_eq![ groups[&0].clone().into_sorted_vec(), vec![12, 22] ];
+        assert_eq![ groups[&1].clone().into_sorted_vec(), vec![97, 877] ];
+
+        // no room at all: the groups are still created
+        let groups = group_topset(items, 0, |x| x % 3, i32::gt);
+        assert_eq![ groups.len(), 3 ];
+        assert!( groups.values().all(|top| top.is_empty()) );
+        assert!( group_topset(Vec::<i32>::new(), 2, |x| x % 3, i32::gt).is_empty() );
+    }
+}
@@ -1,7 +1,18 @@
-use std::cmp::Ordering;
-use std::fmt::{Debug, Formatter};
-use std::mem;
-use crate::TopSet;
+use alloc::boxed::Box;
+use alloc::collections::{BinaryHeap, VecDeque};
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::cmp::Ordering;
+use core::fmt::{Debug, Display, Formatter};
+use core::mem;
+use core::ops::{Deref, DerefMut, Sub};
+use core::hash::{Hash, Hasher};
+use core::iter::{Empty, Sum};
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::collections::hash_map::Entry;
+use crate::{greatest_f32, greatest_f64, MaybeNan, NoHook, TopSet, TopSetBuilder};
 
 impl<X,C> TopSet<X,C>
     where C: Fn(&X,&X) -> bool
@@ -21,6 +32,14 @@ impl<X,C> TopSet<X,C>
     /// This function should always returns the same result
     /// when dealing with the same items or results are unpredictable.
     ///
+    /// For floats, a challenge such as [`f64::gt`] is not a total ordering if a `NaN`
+    /// is inserted. This is not checked here: it would require specialization, which
+    /// is not available on stable Rust. Use [`TopSet::new_float`] to check it (in debug
+    /// builds) or [`TopSet::new_total_f64`] to order the `NaN`s too.
+    ///
+    /// A top set of size `0` never stores anything: no item is a candidate,
+    /// every inserted item is given back and the top set stays empty.
+    ///
     /// # Example
     /// Collecting the 5 greatest integers is performed by using a
     /// topset with `n = 5` and `beat = i32::gt`.
@@ -30,17 +49,53 @@ impl<X,C> TopSet<X,C>
     /// ```
     pub fn new(n: usize, beat: C) -> Self
     {
-        Self {
-            heap: Vec::with_capacity(n),
-            count: n,
-            beat
-        }
+        Self::with_storage(n, beat, Vec::with_capacity(n))
+    }
+
+    /// Creates a new top set whose ties are broken by the insertion order.
+    ///
+    /// When two items do not beat each other, the one inserted first beats
+    /// the other one. This tie-break is used everywhere (eviction, heap layout
+    /// and sorting) so the top set is fully determined by the sequence of
+    /// insertions: identical builds lead to identical iterations.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::new_deterministic(2, |a: &(u32,char), b| a.0 > b.0);
+    /// topset.extend(vec![(1,'a'), (3,'b'), (3,'c'), (3,'d')]);
+    /// assert_eq!( topset.into_sorted_vec(), vec![(3,'c'), (3,'b')]);
+    /// ```
+    pub fn new_deterministic(n: usize, beat: C) -> Self
+    {
+        Self { ties: Ties::Oldest, stamps: Vec::with_capacity(n), ..Self::new(n, beat) }
+    }
+
+    /// Creates a new top set whose ties are broken by the arrival (first in, first out).
+    ///
+    /// When two items do not beat each other, the one inserted last beats
+    /// the other one. So, among tied items, the oldest ones are evicted first
+    /// and popped first. As for [`Self::new_deterministic`], this tie-break is
+    /// used everywhere and does not change anything for strictly ordered items.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::new_fifo(2, |a: &(u32,char), b| a.0 > b.0);
+    /// topset.extend(vec![(1,'a'), (3,'b'), (3,'c'), (3,'d')]);
+    /// assert_eq!( topset.into_sorted_vec(), vec![(3,'c'), (3,'d')]);
+    /// ```
+    pub fn new_fifo(n: usize, beat: C) -> Self
+    {
+        Self { ties: Ties::Newest, stamps: Vec::with_capacity(n), ..Self::new(n, beat) }
     }
 
     /// Creates a new top set with a selecting closure and an initial set of items.
     ///
     /// If the initial set contains more than `n` elements, only the `n` greatest ones
     /// (according to `beat` challenging function) are stored.
+    /// The heap is built at once (in linear time) from the first `n` items
+    /// and then the remaining ones are inserted one by one (see [`Self::refill`]).
     ///
     /// # Example
     /// ```
@@ -53,10 +108,87 @@ impl<X,C> TopSet<X,C>
     pub fn with_init<I: IntoIterator<Item=X>>(n: usize, beat: C, init: I) -> Self
     {
         let mut top = Self::new(n, beat);
-        top.extend(init);
+        top.refill(init);
+        top
+    }
+
+    /// Creates a new top set from several sorted streams.
+    ///
+    /// Each stream should be sorted from the greatest item to the lowest one
+    /// (according to `beat`), e.g. the content of sorted files. Since the items
+    /// of a stream only get lower, a stream is abandoned as soon as one of its items
+    /// is not a candidate: at most `n+1` items are read from each stream.
+    /// The result is the same as inserting all the items.
+    ///
+    /// If a stream is not sorted, some of its items could be missed.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let streams = vec![ vec![9,5,1], vec![8,7,6,2], vec![4,3] ];
+    /// let topset = TopSet::from_sorted_streams(3, u32::gt, streams);
+    /// assert_eq!( topset.into_sorted_vec(), vec![7,8,9]);
+    /// ```
+    pub fn from_sorted_streams<S>(n: usize, beat: C, streams: S) -> Self
+        where S: IntoIterator, S::Item: IntoIterator<Item=X>
+    {
+        let mut top = Self::new(n, beat);
+        for stream in streams {
+            for x in stream {
+                if !top.is_candidate(&x) {
+                    // the next items of this stream are not better
+                    break;
+                }
+                top.insert(x);
+            }
+        }
+        top
+    }
+
+    /// Rebuilds a top set from an arbitrary vector of items.
+    ///
+    /// This is the inverse of [`Self::into_vec`]: no order is assumed in `data`.
+    /// If `data` contains more than `n` items, only the `n` greatest ones
+    /// (according to `beat`) are kept. The vector is reused as storage and
+    /// the whole rebuild is done in linear time.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::rebuild(3, u32::gt, vec![7,5,6,9,4,2,3]);
+    /// assert_eq!( topset.pop(), Some(6));
+    /// assert_eq!( topset.pop(), Some(7));
+    /// assert_eq!( topset.pop(), Some(9));
+    /// assert_eq!( topset.pop(), None);
+    /// ```
+    pub fn rebuild(n: usize, beat: C, data: Vec<X>) -> Self
+    {
+        let mut top = Self::with_storage(n, beat, Vec::new());
+        top.reset(data);
         top
     }
 
+    /// Builds a top set from its parts (see [`Self::into_parts`]).
+    ///
+    /// The vector `heap` is **not** assumed to be a valid heap: any vector could
+    /// be given and the heap is rebuilt (in linear time), keeping only the `count`
+    /// greatest items. This is the same as [`Self::rebuild`].
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let topset = TopSet::with_init(3, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// let (mut heap, count, beat) = topset.into_parts();
+    /// heap.push(8);
+    /// let topset = TopSet::from_parts(heap, count, beat);
+    /// assert_eq!( topset.into_sorted_vec(), vec![7,8,9]);
+    /// ```
+    #[inline]
+    pub fn from_parts(heap: Vec<X>, count: usize, beat: C) -> Self
+    {
+        Self::rebuild(count, beat, heap)
+    }
+
     /// Check if the top set is empty
     /// # Example
     /// ```
@@ -89,6 +221,8 @@ impl<X,C> TopSet<X,C>
     ///
     /// The capacity limits the number of elements to keep.
     /// This capacity could only change by calling [`resize`].
+    /// It is not related to the allocated storage (see [`Self::reserve`]
+    /// and [`Self::shrink_to_fit`]).
     ///
     /// # Example
     /// ```
@@ -100,6 +234,40 @@ impl<X,C> TopSet<X,C>
     #[inline]
     pub fn capacity(&self) -> usize { self.count }
 
+    /// Checks if there is no more room left
+    ///
+    /// If `true`, any new item should be challenged against the stored ones
+    /// (see [`Self::is_candidate`]). Notice that growing the top set with
+    /// [`Self::resize`] makes room again, so it is no more full.
+    /// The effective capacity (see [`Self::set_effective_capacity`]) is taken into account.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::with_init(2, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// assert!( topset.is_full() );
+    /// topset.resize(3);
+    /// assert!( ! topset.is_full() );
+    /// ```
+    #[inline]
+    pub fn is_full(&self) -> bool { self.heap.len() >= self.limit() }
+
+    /// Gets the number of items which could still be added without any eviction
+    ///
+    /// This is zero when the top set is full (see [`Self::is_full`]), never an underflow.
+    /// The effective capacity (see [`Self::set_effective_capacity`]) is taken into account.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::with_init(5, u32::gt, vec![7,5,6] );
+    /// assert_eq!( topset.capacity_left(), 2 );
+    /// topset.resize(2);
+    /// assert_eq!( topset.capacity_left(), 0 );
+    /// ```
+    #[inline]
+    pub fn capacity_left(&self) -> usize { self.limit().saturating_sub(self.heap.len()) }
+
     /// Read access to the lowest item of the top set
     ///
     /// Notice that it actually returned the _lowest_ one and
@@ -119,9 +287,166 @@ impl<X,C> TopSet<X,C>
     #[inline]
     pub fn peek(&self) -> Option<&X>
     {
+        debug_assert!(self.is_repaired(), "the top set was edited through as_mut_slice without calling repair");
         self.heap.first()
     }
 
+    /// Mutable access to the lowest item of the top set
+    ///
+    /// The returned guard gives access to the lowest item and, when it is dropped,
+    /// the heap is restored (the item is moved at its right place if it was
+    /// modified). `None` is returned if the top set is empty.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::with_init(3, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// if let Some(mut lowest) = topset.peek_mut() {
+    ///     *lowest += 2;
+    /// }
+    /// assert_eq!( topset.peek(), Some(&7) );
+    /// assert_eq!( topset.into_sorted_vec(), vec![7,8,9]);
+    /// ```
+    pub fn peek_mut(&mut self) -> Option<PeekMut<'_,X,C>>
+    {
+        if self.heap.is_empty() { None } else { Some(PeekMut { top: self }) }
+    }
+
+    /// Read access to the greatest item of the top set
+    ///
+    /// The greatest item is not tracked: it is searched among the leaves
+    /// of the heap (i.e. half of the items), so this is in `O(n)`.
+    /// For a single item, this is the same as [`Self::peek`].
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let topset = TopSet::with_init(3, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// assert_eq!( topset.peek(), Some(&6) );
+    /// assert_eq!( topset.peek_best(), Some(&9) );
+    /// ```
+    pub fn peek_best(&self) -> Option<&X>
+    {
+        self.best_index().map(|i| &self.heap[i])
+    }
+
+    /// Computes the gap between the best and the lowest stored items.
+    ///
+    /// The difference is computed as `best - lowest`, so it is non-negative
+    /// when selecting the greatest items (with [`PartialOrd::gt`]) but
+    /// non-positive when selecting the lowest ones (with [`PartialOrd::lt`]).
+    /// For a single item, this is `x - x`. `None` is returned for an empty top set.
+    ///
+    /// See [`Self::spread_by`] for types without subtraction.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let topset = TopSet::with_init(3, i32::gt, vec![7,5,6,9,4,2,3] );
+    /// assert_eq!( topset.spread(), Some(3) );
+    /// ```
+    #[inline]
+    pub fn spread(&self) -> Option<X>
+        where X: Sub<Output=X> + Clone
+    {
+        self.spread_by(|best, lowest| best.clone() - lowest.clone())
+    }
+
+    /// Computes the gap between the best and the lowest stored items with a difference function.
+    ///
+    /// The function `diff` is called with the best item first and the lowest one second.
+    /// `None` is returned for an empty top set.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let topset = TopSet::with_init(3, |a: &&str, b: &&str| a.len() > b.len(), vec!["a","abcd","ab","abc"] );
+    /// assert_eq!( topset.spread_by(|best, lowest| best.len() - lowest.len()), Some(2) );
+    /// ```
+    pub fn spread_by<D,F>(&self, diff: F) -> Option<D>
+        where F: Fn(&X,&X) -> D
+    {
+        let lowest = self.peek()?;
+        let best = &self.heap[self.best_index()?];
+        Some(diff(best, lowest))
+    }
+
+    /// Reduces all the stored items into a single value.
+    ///
+    /// The items are visited in an unspecified order (see [`Self::iter`]).
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let topset = TopSet::with_init(3, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// assert_eq!( topset.fold(0, |acc, x| acc.max(x % 3)), 1 );
+    /// ```
+    #[inline]
+    pub fn fold<B,F>(&self, init: B, f: F) -> B
+        where F: FnMut(B, &X) -> B
+    {
+        self.heap.iter().fold(init, f)
+    }
+
+    /// Sums all the stored items.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// # use std::time::Duration;
+    /// let latencies = vec![7,5,6,9,4,2,3].into_iter().map(Duration::from_millis);
+    /// let topset = TopSet::with_init(3, Duration::gt, latencies );
+    /// assert_eq!( topset.sum::<Duration>(), Duration::from_millis(22) );
+    /// ```
+    #[inline]
+    pub fn sum<'a,S>(&'a self) -> S
+        where S: Sum<&'a X>
+    {
+        self.heap.iter().sum()
+    }
+
+    /// Computes the average of all the stored items.
+    ///
+    /// `None` is returned for an empty top set.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let topset = TopSet::with_init(4, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// assert_eq!( topset.mean(), Some(6.75) );
+    /// ```
+    pub fn mean(&self) -> Option<f64>
+        where X: Copy + Into<f64>
+    {
+        if self.heap.is_empty() {
+            None
+        } else {
+            Some(self.fold(0., |sum, &x| sum + x.into()) / self.heap.len() as f64)
+        }
+    }
+
+    /// Get the item at a fractional rank
+    ///
+    /// The rank `f` goes from `0.0` (the _lowest_ stored item) to `1.0` (the _best_ one)
+    /// and is clamped into this range. The item is found by a partial selection
+    /// (average linear time) over a buffer of indices, so the top set is left untouched.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let topset = TopSet::with_init(5, u32::gt, vec![1,7,5,6,9,4,2,3] );
+    /// assert_eq!( topset.value_at_fraction(0.), Some(&4) );
+    /// assert_eq!( topset.value_at_fraction(0.5), Some(&6) );
+    /// assert_eq!( topset.value_at_fraction(0.9), Some(&9) );
+    /// ```
+    pub fn value_at_fraction(&self, f: f64) -> Option<&X>
+    {
+        let last = self.heap.len().checked_sub(1)?;
+        // rounded to the nearest rank (f64::round is not available without std)
+        let rank = (f.clamp(0., 1.) * last as f64 + 0.5) as usize;
+        self.get_sorted(rank)
+    }
+
     /// Checks if an item will be inserted or not
     ///
     /// If it `true` is returned, it means that a call to [`Self::insert`]
@@ -143,367 +468,3387 @@ impl<X,C> TopSet<X,C>
     /// ```
     #[inline]
     pub fn is_candidate(&self, x: &X) -> bool {
-        self.heap.len() < self.count || self.beat(x, self.peek().unwrap())
+        // with no room at all (capacity 0), there is no lowest item to challenge
+        !self.is_full() || self.peek().is_some_and(|lowest| self.wins_as_newest(x, lowest))
     }
 
-    /// Iterate over all the top selected items.
-    ///
-    /// The iterator is **not** sorted. A sorted iteration
-    /// could be obtained by iterative call to [`Self::pop`]
-    /// or by using [`Self::into_iter_sorted`].
+    /// Checks if an item will be inserted or not, with a value cutoff
     ///
-    /// To get a vector with all elements, instead of using this
-    /// iterator, consider [`Self::into_vec`].
+    /// This is the counterpart of [`Self::is_candidate`] for [`Self::insert_above`]:
+    /// the item should also be at least as good as `floor`.
     ///
     /// # Example
     /// ```
     /// # use topset::TopSet;
-    /// // this topset contains { 7, 9 }
-    /// let topset = TopSet::with_init(2, u32::gt, vec![7,5,6,9,4,2,3] );
-    /// let elts = topset.iter().cloned().collect::<Vec<_>>();
+    /// // this topset contains { 7 } with one room left
+    /// let topset = TopSet::with_init(2, u32::gt, vec![7] );
+    /// assert!( topset.is_candidate_above(&6, &5) );
+    /// assert!( topset.is_candidate_above(&5, &5) );
+    /// assert!( ! topset.is_candidate_above(&4, &5) );
     /// ```
-    #[inline]
-    pub fn iter(&self) -> impl Iterator<Item=&X>
+    pub fn is_candidate_above(&self, x: &X, floor: &X) -> bool
     {
-        self.heap.iter()
+        !self.beat(floor, x) && self.is_candidate(x)
     }
 
-    /// Gets all the top set elements in a vector.
+    /// Checks if an item will be inserted or not, counting the ties as candidates
     ///
-    /// This vector is **not** sorted.
-    /// See [`Self::into_sorted_vec`] if a sorted result is expected.
+    /// This is the counterpart of [`Self::is_candidate`] for [`Self::insert_inclusive`]:
+    /// an item which ties with the lowest one (none beats the other) is also a candidate.
     ///
     /// # Example
     /// ```
     /// # use topset::TopSet;
     /// // this topset contains { 7, 9 }
     /// let topset = TopSet::with_init(2, u32::gt, vec![7,5,6,9,4,2,3] );
-    /// let elts = topset.into_vec();
+    /// assert!( topset.is_candidate_inclusive(&8) );
+    /// assert!( topset.is_candidate_inclusive(&7) );
+    /// assert!( ! topset.is_candidate_inclusive(&6) );
     /// ```
     #[inline]
-    pub fn into_vec(self) -> Vec<X> { self.heap }
+    pub fn is_candidate_inclusive(&self, x: &X) -> bool {
+        !self.is_full() || self.peek().is_some_and(|lowest| !self.beat(lowest, x))
+    }
 
-    /// Insert a new item.
+    /// Previews the item which would be removed by an insertion
     ///
-    /// If the top set is not filled (i.e. its length is less than its capacity),
-    /// the item is simply added and `None` is returned.
-    ///
-    /// If there is no more room, then one item should be rejected:
-    /// * if the new item is better than some already stored ones, it is added
-    /// and the removed item is returned
-    /// * if the new item is worse than all the stored ones, it is returned
+    /// If the top set is full and `x` is a candidate (see [`Self::is_candidate`]),
+    /// the current lowest item would be removed by inserting `x` and so it is
+    /// returned. Otherwise (some room left or `x` rejected), `None` is returned.
     ///
     /// # Example
     /// ```
     /// # use topset::TopSet;
-    /// let mut topset = TopSet::new(2, u32::gt);
-    /// assert_eq!( topset.insert(7), None);
-    /// assert_eq!( topset.insert(8), None);
-    /// assert_eq!( topset.insert(9), Some(7));
-    /// assert_eq!( topset.insert(6), Some(6));
+    /// // this topset contains { 7, 9 }
+    /// let topset = TopSet::with_init(2, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// assert_eq!( topset.would_evict(&8), Some(&7) );
+    /// assert_eq!( topset.would_evict(&6), None );
     /// ```
-    pub fn insert(&mut self, mut x: X) -> Option<X>
+    pub fn would_evict(&self, x: &X) -> Option<&X>
     {
-        if self.heap.len() < self.count {
-            // some room left, so nothing to remove
-            self.heap.push(x);
-            self.percolate_up(self.heap.len()-1);
+        if !self.is_full() {
             None
         } else {
-            // SAFETY: if the heap is empty when self.count != 0, then we fall
-            // in the previous if condition (so, here, get_unchecked is safe)
-            if self.count != 0 && self.beat(&x, unsafe { self.heap.get_unchecked(0) }) {
-                // put the greatest the deepest: the new one should be kept
-                mem::swap(&mut x, &mut self.heap[0]);
-                self.percolate_down(0);
-            }
-            Some(x)
+            self.peek().filter(|lowest| self.wins_as_newest(x, lowest))
         }
     }
 
-    /// Converts this topset into a sorted iterator
-    ///
-    /// Notice that the _lowest_ item of the top set is the
-    /// first one. The _greatest_ item is the last one.
+    /// Checks if an item is stored in the top set
     ///
     /// # Example
     /// ```
     /// # use topset::TopSet;
-    /// // this topset contains { 7, 9 }
     /// let topset = TopSet::with_init(2, u32::gt, vec![7,5,6,9,4,2,3] );
-    /// let mut iter = topset.into_iter_sorted();
-    /// assert_eq!( iter.next(), Some(7));
-    /// assert_eq!( iter.next(), Some(9));
-    /// assert_eq!( iter.next(), None);
+    /// assert!( topset.contains(&7) );
+    /// assert!( ! topset.contains(&6) );
     /// ```
     #[inline]
-    pub fn into_iter_sorted(self) -> crate::iter::IntoIterSorted<X,C> {
-        self.into()
+    pub fn contains(&self, x: &X) -> bool
+        where X: PartialEq
+    {
+        self.heap.contains(x)
     }
 
-    /// Returns the topset in a sorted vector.
+    /// Gets the rank that an item would have in the top set
     ///
-    /// The first element of the vector is the _lowest_ item of the top set
-    /// and the last one is the _greatest_ one.
+    /// This is the number of stored items which beat `x`, so `0` means that
+    /// `x` would be the new greatest item. Unlike [`Self::min_n_to_include`],
+    /// the ties are not counted.
     ///
     /// # Example
     /// ```
     /// # use topset::TopSet;
-    /// // this topset contains { 7, 9 }
-    /// let topset = TopSet::with_init(3, u32::gt, vec![1,2,7,4,7,5,6,9,4,2,3] );
-    /// assert_eq!( topset.into_sorted_vec(), vec![7,7,9]);
+    /// // this topset contains { 6, 7, 9 }
+    /// let topset = TopSet::with_init(3, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// assert_eq!( topset.rank_of(&10), 0 );
+    /// assert_eq!( topset.rank_of(&7), 1 );
+    /// assert_eq!( topset.rank_of(&1), 3 );
     /// ```
-    pub fn into_sorted_vec(mut self) -> Vec<X>
-        where X:PartialEq
+    pub fn rank_of(&self, x: &X) -> usize
     {
-        self.heap.sort_unstable_by(|a,b| {
-            if *a == *b {
-                Ordering::Equal
-            } else if (self.beat)(a,b) {
-                Ordering::Greater
-            } else {
-                Ordering::Less
-            }
-        });
-        self.heap
+        self.heap.iter().filter(|e| self.beat(e, x)).count()
     }
 
-    /// Clears the binary heap, returning an iterator over the removed elements in arbitrary order.
-    /// If the iterator is dropped before being fully consumed, it drops the remaining elements in arbitrary order.
+    /// Counts the stored items which beat `x`
     ///
-    /// The returned iterator keeps a mutable borrow on the heap to optimize its implementation.
+    /// This is the same as [`Self::rank_of`]. Only the challenge is used and
+    /// the heap is scanned in `O(n)`. The items which tie with `x` are neither
+    /// counted here nor by [`Self::count_worse_than`].
     ///
     /// # Example
     /// ```
     /// # use topset::TopSet;
-    /// let mut topset = TopSet::with_init(4, u32::gt, vec![7,5,6,9,4,2,3] );
-    /// let _ = topset.drain();
-    /// assert! (topset.is_empty());
+    /// // this topset contains { 6, 7, 9 }
+    /// let topset = TopSet::with_init(3, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// assert_eq!( topset.count_better_than(&7), 1 );
+    /// assert_eq!( topset.count_better_than(&8), 1 );
     /// ```
     #[inline]
-    pub fn drain(&mut self) -> std::vec::Drain<X> {
-        self.heap.drain(..)
-    }
+    pub fn count_better_than(&self, x: &X) -> usize { self.rank_of(x) }
 
-    /// Resize the top set
+    /// Counts the stored items which are beaten by `x`
     ///
-    /// If the size decreases, then the lowest items are removed.
-    /// If the size increases, nothing else happens but there is still more room
-    /// for next insertions.
+    /// Only the challenge is used and the heap is scanned in `O(n)`.
+    /// See [`Self::count_better_than`].
     ///
     /// # Example
     /// ```
     /// # use topset::TopSet;
-    /// let mut topset = TopSet::with_init(4, u32::gt, vec![7,5,6,9,4,2,3] );
-    ///
-    /// // the topset contains { 7, 5, 6, 9 }
-    /// assert_eq! (topset.peek(), Some(&5));
+    /// // this topset contains { 6, 7, 9 }: 8 beats 2 of them
+    /// let topset = TopSet::with_init(3, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// assert_eq!( topset.count_worse_than(&8), 2 );
+    /// assert_eq!( topset.count_worse_than(&7), 1 );
+    /// ```
+    pub fn count_worse_than(&self, x: &X) -> usize
+    {
+        self.heap.iter().filter(|e| self.beat(x, e)).count()
+    }
+
+    /// Gets the item at a given position in the sorted top set
     ///
-    /// topset.resize(2);
-    /// // the topset contains { 7, 9 }
-    /// assert_eq! (topset.peek(), Some(&7));
+    /// The position `0` is the _lowest_ item and `len()-1` is the _greatest_ one,
+    /// as in [`Self::into_sorted_vec`]. If `i` is out of range, `None` is returned.
     ///
-    /// // try to add 1 but no more room left
-    /// assert_eq!( topset.insert(1), Some(1) );
+    /// The top set is not modified: the item is found by a selection over a buffer
+    /// of indices, which costs `O(n)` on average for each call.
     ///
-    /// topset.resize(3); // grows by one
-    /// assert_eq!( topset.insert(1), None ); // one room left
-    /// assert_eq!( topset.insert(2), Some(1) ); // but now, is full
-    /// // at this point, the topset contains { 7, 9, 2 }
+    /// # Example
     /// ```
-    pub fn resize(&mut self, n: usize)
+    /// # use topset::TopSet;
+    /// // this topset contains { 6, 7, 9 }
+    /// let topset = TopSet::with_init(3, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// assert_eq!( topset.get_sorted(0), Some(&6) );
+    /// assert_eq!( topset.get_sorted(2), Some(&9) );
+    /// assert_eq!( topset.get_sorted(3), None );
+    /// // the item which is beaten by exactly one other
+    /// assert_eq!( topset.get_sorted(topset.len() - 1 - topset.rank_of(&7)), Some(&7) );
+    /// ```
+    pub fn get_sorted(&self, i: usize) -> Option<&X>
     {
-        if self.count < n {
-            self.heap.reserve(n - self.count);
-        } else {
-            while self.heap.len() > n {
-                self.pop();
-            }
+        if i >= self.heap.len() {
+            return None;
         }
-        self.count = n;
+        let mut indices = (0..self.heap.len()).collect::<Vec<_>>();
+        let (_, &mut nth, _) = indices.select_nth_unstable_by(i, |&a, &b| duel_ordering(&self.beat, &self.heap[a], &self.heap[b])
+            .then(self.tie_ordering(a, b)));
+        Some(&self.heap[nth])
     }
 
-    /// Pop the lowest item of the top set
+    /// Gets the minimal capacity needed to include an item
     ///
-    /// Remove and return the _lowest_ item of the top set.
-    /// After this call, there is one more room for a item.
+    /// This is the rank that `x` would have among the stored items:
+    /// the number of stored items which are not beaten by `x` (so the ties
+    /// are counted as beating `x`), plus one.
+    /// If `x` beats all the stored items, `1` is returned.
     ///
-    /// This method is the only way to get the top elements
-    /// in a sorted way (from the lowest to the best).
-    /// Resize the top set
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// // this topset contains { 6, 7, 9 }
+    /// let topset = TopSet::with_init(3, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// assert_eq!( topset.min_n_to_include(&10), 1 );
+    /// assert_eq!( topset.min_n_to_include(&8), 2 );
+    /// assert_eq!( topset.min_n_to_include(&7), 3 );
+    /// ```
+    pub fn min_n_to_include(&self, x: &X) -> usize
+    {
+        self.heap.iter().filter(|e| !self.beat(x, e)).count() + 1
+    }
+
+    /// Iterate over all the top selected items.
     ///
-    /// If the size decreases, then the lowest items are removed.
-    /// If the size increases, nothing else happens but there is still more room
-    /// for next insertions.
+    /// The iterator is **not** sorted. A sorted iteration
+    /// could be obtained by iterative call to [`Self::pop`]
+    /// or by using [`Self::into_iter_sorted`].
+    ///
+    /// To get a vector with all elements, instead of using this
+    /// iterator, consider [`Self::into_vec`].
     ///
     /// # Example
     /// ```
     /// # use topset::TopSet;
-    /// let mut topset = TopSet::with_init(2, u32::gt, vec![7,5,6,9,4,2,3] );
-    ///
-    /// assert_eq! (topset.pop(), Some(7));
-    /// assert_eq! (topset.pop(), Some(9));
-    /// assert_eq! (topset.pop(), None);
+    /// // this topset contains { 7, 9 }
+    /// let topset = TopSet::with_init(2, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// let elts = topset.iter().cloned().collect::<Vec<_>>();
     /// ```
-    pub fn pop(&mut self) -> Option<X>
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item=&X>
     {
-        match self.heap.len() {
-            0 => None,
-            1|2 => Some(self.heap.swap_remove(0)),
-            _ => {
-                let pop = self.heap.swap_remove(0);
-                self.percolate_down(0);
-                Some(pop)
-            }
-        }
+        self.heap.iter()
     }
 
-    /// Removes all the elements in the top set
+    /// Gives a read-only access to all the stored items (**not** sorted).
+    ///
+    /// The items are given in the internal heap order, without any copy.
+    /// Modifying them would break this order: a mutable access is only available
+    /// through [`Self::as_mut_slice`] which requires a call to [`Self::repair`].
+    ///
     /// # Example
     /// ```
     /// # use topset::TopSet;
-    /// let mut topset = TopSet::with_init(2, u32::gt, vec![7,5,6,9,4,2,3] );
-    ///
-    /// assert_eq! (topset.len(), 2);
-    /// topset.clear();
-    /// assert_eq!( topset.len(), 0)
+    /// let topset = TopSet::with_init(2, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// assert_eq!( topset.as_slice().len(), 2 );
+    /// assert_eq!( topset.as_slice().iter().sum::<u32>(), 16 );
     /// ```
-    #[inline] pub fn clear(&mut self) { self.heap.clear() }
+    #[inline]
+    pub fn as_slice(&self) -> &[X] { &self.heap }
 
-    /// Checks if an element beats the other.
+    /// Iterate over all the top selected items in sorted order.
     ///
-    /// It does not related to the current elements in the topset but
-    /// refers only to the comparing function (the _beat_).
+    /// The items are visited from the _lowest_ to the _greatest_ without
+    /// modifying the top set, so it could be called repeatedly.
+    /// Each call sorts a buffer of indices, which costs `O(n log n)`.
     ///
     /// # Example
     /// ```
     /// # use topset::TopSet;
-    /// let mut topset = TopSet::with_init(2, u32::gt, vec![7,5,6,9,4,2,3] );
-    ///
-    /// assert! ( topset.beat(&4, &3));
-    /// assert! ( ! topset.beat(&4, &7));
+    /// let topset = TopSet::with_init(3, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// assert_eq!( topset.iter_sorted().collect::<Vec<_>>(), vec![&6,&7,&9]);
+    /// assert_eq!( topset.len(), 3);
     /// ```
-    #[inline] pub fn beat(&self, a:&X, b:&X) -> bool { (self.beat)(a,b) }
+    pub fn iter_sorted(&self) -> impl Iterator<Item=&X>
+    {
+        let mut order: Vec<usize> = (0..self.heap.len()).collect();
+        order.sort_by(|&i,&j| duel_ordering(&self.beat, &self.heap[i], &self.heap[j])
+            .then(self.tie_ordering(i, j)));
+        order.into_iter().map(|i| &self.heap[i])
+    }
 
-    // internal stuff
-    // move i up (to the best)
-    fn percolate_up(&mut self, mut i: usize)
+    /// Checks if the top set contains exactly the expected sorted items.
+    ///
+    /// The stored items are cloned and sorted from the _lowest_ to the
+    /// _greatest_ (as [`Self::into_sorted_vec`] does) before being compared
+    /// to `expected`. Since it is a multiset comparison, duplicated items
+    /// should appear as many times in `expected` as in the top set.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let topset = TopSet::with_init(3, u32::gt, vec![7,5,9,4,9,2,3] );
+    /// assert!( topset.eq_sorted(&[7,9,9]) );
+    /// assert!( ! topset.eq_sorted(&[7,9]) );
+    /// assert!( ! topset.eq_sorted(&[9,9,7]) );
+    /// ```
+    pub fn eq_sorted(&self, expected: &[X]) -> bool
+        where X: PartialEq + Clone
     {
-        while i > 0 { // so has a parent (not root)
-            let parent = (i-1)/2;
-            // put the greatest the deepest
-            if self.beat(&self.heap[parent], &self.heap[i]) {
-                self.heap.swap(parent, i);
-                i = parent;
-            } else {
-                break;
-            }
+        if self.heap.len() != expected.len() {
+            return false;
         }
+        self.to_sorted_vec() == expected
     }
 
-    // internal stuff
-    // move i as deep as possible
-    fn percolate_down(&mut self, mut i: usize)
+    /// Counts the stored items per bucket.
+    ///
+    /// Each item is put in the bucket given by the `bucket` function.
+    /// The top set is left untouched.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let topset = TopSet::with_init(4, u32::gt, vec![17,5,26,9,14,2,3] );
+    /// let tens = topset.histogram(|x| x / 10);
+    /// assert_eq!( tens[&0], 1 );
+    /// assert_eq!( tens[&1], 2 );
+    /// assert_eq!( tens[&2], 1 );
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn histogram<K,F>(&self, bucket: F) -> HashMap<K,usize>
+        where K: Eq + Hash, F: Fn(&X) -> K
     {
-        loop {
-            let mut child = 2*i+1;
-            if child < self.heap.len()-1 {
-                // to put the greatest the deepest -> select the greatest child
-                if self.beat(&self.heap[child], &self.heap[child+1]) {
-                    child += 1;
-                }
-                // put the greatest the deepest
-                if self.beat(&self.heap[i], &self.heap[child]) {
-                    self.heap.swap(i, child);
-                    i = child;
-                } else {
-                    break;
-                }
-            } else {
-                if (child == self.heap.len() - 1) && self.beat(&self.heap[i], &self.heap[child]) {
-                    // only one child
-                    self.heap.swap(i, child);
-                }
-                // end of heap
-                break;
-            }
-        }
+        let mut histogram = HashMap::new();
+        self.heap.iter().for_each(|x| *histogram.entry(bucket(x)).or_insert(0) += 1);
+        histogram
     }
-}
 
+    /// Get the stored items which are not in a baseline.
+    ///
+    /// The baseline is defined by the membership test `is_baseline`:
+    /// the stored items for which it returns `false` are returned (**not** sorted).
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let yesterday = [9, 7, 6];
+    /// let topset = TopSet::with_init(3, u32::gt, vec![7,5,8,9,4,2,3] );
+    /// assert_eq!( topset.novel_against(|x| yesterday.contains(x)), vec![&8] );
+    /// ```
+    pub fn novel_against<F>(&self, is_baseline: F) -> Vec<&X>
+        where F: Fn(&X) -> bool
+    {
+        self.heap.iter().filter(|x| !is_baseline(x)).collect()
+    }
 
-impl<X,C> IntoIterator for TopSet<X,C>
-    where C: Fn(&X,&X) -> bool
-{
-    type Item = X;
-    type IntoIter = <Vec<X> as IntoIterator>::IntoIter;
+    /// Gets all the top set elements in a vector.
+    ///
+    /// This vector is **not** sorted.
+    /// See [`Self::into_sorted_vec`] if a sorted result is expected.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// // this topset contains { 7, 9 }
+    /// let topset = TopSet::with_init(2, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// let elts = topset.into_vec();
+    /// ```
+    #[inline]
+    pub fn into_vec(self) -> Vec<X> { self.heap }
 
+    /// Splits the top set into its parts: the heap buffer, the capacity and the challenge.
+    ///
+    /// The buffer is ordered as a heap (the lowest item first) and keeps its
+    /// allocated storage. The tie breaking policy (see [`Self::new_deterministic`])
+    /// and the effective capacity (see [`Self::set_effective_capacity`]) are not
+    /// part of it and are lost. The top set could be built again by [`Self::from_parts`].
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let topset = TopSet::with_init(2, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// let (heap, count, beat) = topset.into_parts();
+    /// assert_eq!( heap, vec![7,9]);
+    /// assert_eq!( count, 2);
+    /// assert!( beat(&3, &2) );
+    /// ```
     #[inline]
-    fn into_iter(self) -> Self::IntoIter {
-        self.heap.into_iter()
-    }
-}
+    pub fn into_parts(self) -> (Vec<X>, usize, C) { (self.heap, self.count, self.beat) }
 
-impl<'a,X,C> IntoIterator for &'a TopSet<X,C>
-    where C: Fn(&X,&X) -> bool
-{
-    type Item = &'a X;
-    type IntoIter = <&'a Vec<X> as IntoIterator>::IntoIter;
+    /// Gets all the top set elements in a vector ordered as a max-heap.
+    ///
+    /// The items are rearranged (in linear time) so that the vector is a valid
+    /// max-heap according to `Ord`, so [`std::collections::BinaryHeap::from`]
+    /// has no more work to do with it.
+    ///
+    /// Note that the items are _selected_ with the challenge of the top set
+    /// but they are _arranged_ with `Ord`, which may be unrelated
+    /// (e.g. a top set of the lowest items handed to a `BinaryHeap`
+    /// pops the greatest of them first).
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// # use std::collections::BinaryHeap;
+    /// // this topset contains { 6, 7, 9 }
+    /// let topset = TopSet::with_init(3, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// let mut heap = BinaryHeap::from(topset.into_max_heap_vec());
+    /// assert_eq!( heap.pop(), Some(9) );
+    /// ```
+    pub fn into_max_heap_vec(self) -> Vec<X>
+        where X: Ord
+    {
+        BinaryHeap::from(self.heap).into_vec()
+    }
 
+    /// Insert a new item.
+    ///
+    /// If the top set is not filled (i.e. its length is less than its capacity),
+    /// the item is simply added and `None` is returned.
+    ///
+    /// If there is no more room, then one item should be rejected:
+    /// * if the new item is better than some already stored ones, it is added
+    ///   and the removed item is returned
+    /// * if the new item is worse than all the stored ones, it is returned
+    ///
+    /// When the top set is full, a rejected item (including an item which only
+    /// ties with the lowest stored one) costs exactly one duel against the lowest
+    /// item: the heap is not touched, so streams of equal items stay cheap.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::new(2, u32::gt);
+    /// assert_eq!( topset.insert(7), None);
+    /// assert_eq!( topset.insert(8), None);
+    /// assert_eq!( topset.insert(9), Some(7));
+    /// assert_eq!( topset.insert(6), Some(6));
+    /// ```
     #[inline]
-    fn into_iter(self) -> Self::IntoIter {
-        self.heap.iter()
+    pub fn insert(&mut self, x: X) -> Option<X>
+    {
+        match self.push(x) {
+            Outcome::Added => None,
+            Outcome::Evicted(x, _) | Outcome::Rejected(x) => Some(x)
+        }
     }
-}
 
-impl<X,C> Extend<X> for TopSet<X,C>
-    where C: Fn(&X,&X) -> bool
-{
+    /// Insert a new item only if it reaches a value cutoff.
+    ///
+    /// The item `x` is rejected (and so returned) if it is beaten by `floor`,
+    /// even if there is some room left. Otherwise (`x` beats or ties with `floor`),
+    /// it is inserted as with [`Self::insert`].
+    ///
+    /// The cutoff is not stored in the top set: [`Self::is_candidate`] ignores it,
+    /// so use [`Self::is_candidate_above`] with the same `floor` instead.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// // the top 3 scores, but only those which are at least 50
+    /// let mut topset = TopSet::new(3, u32::gt);
+    /// assert_eq!( topset.insert_above(70, &50), None);
+    /// assert_eq!( topset.insert_above(40, &50), Some(40));
+    /// assert_eq!( topset.insert_above(50, &50), None);
+    /// assert_eq!( topset.into_sorted_vec(), vec![50,70]);
+    /// ```
     #[inline]
-    fn extend<T: IntoIterator<Item=X>>(&mut self, iter: T) {
-        iter.into_iter().for_each(|x| { self.insert(x); } )
+    pub fn insert_above(&mut self, x: X, floor: &X) -> Option<X>
+    {
+        if self.beat(floor, &x) {
+            Some(x)
+        } else {
+            self.insert(x)
+        }
     }
-}
 
-
-impl<X,C> Debug for TopSet<X,C>
-    where X:Debug, C: Fn(&X,&X) -> bool
-{
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        self.heap.fmt(f)
+    /// Insert a new item, which replaces the lowest one on a tie.
+    ///
+    /// With [`Self::insert`], a new item which ties with the lowest stored one
+    /// (none beats the other) is rejected: the incumbent is kept. Here, the new
+    /// item takes its place and the incumbent is returned instead, which keeps
+    /// the latest items among the equal ones (see [`Self::is_candidate_inclusive`]).
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// // (player, score): the latest players at the lowest kept score stay in
+    /// let mut topset = TopSet::new(2, |a: &(char,u32), b: &(char,u32)| a.1 > b.1);
+    /// topset.extend(vec![('a',5), ('b',7)]);
+    /// assert_eq!( topset.insert(('c',5)), Some(('c',5)));
+    /// assert_eq!( topset.insert_inclusive(('c',5)), Some(('a',5)));
+    /// assert_eq!( topset.insert_inclusive(('d',4)), Some(('d',4)));
+    /// assert_eq!( topset.into_sorted_vec(), vec![('c',5), ('b',7)]);
+    /// ```
+    pub fn insert_inclusive(&mut self, x: X) -> Option<X>
+    {
+        match self.peek() {
+            Some(lowest) if self.is_full() && !self.beat(lowest, &x) => {
+                match self.replace_lowest(x) {
+                    Outcome::Added => None,
+                    Outcome::Evicted(x, _) | Outcome::Rejected(x) => Some(x)
+                }
+            }
+            _ => self.insert(x)
+        }
     }
-}
-
-
-
-#[cfg(test)]
-mod tests {
-    use crate::iter::TopSetReducing;
-    use crate::TopSet;
 
-    #[test]
-    fn lowest_cost()
+    /// Insert a new item and reports how long the removed one survived.
+    ///
+    /// This behaves exactly as [`Self::insert`] but the removed item (if any)
+    /// is paired with the number of insertions it withstood since it was itself
+    /// inserted into the top set. A rejected new item survived no insertion.
+    ///
+    /// The ages are tracked from the first call: the items stored before
+    /// are considered as inserted just before it.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::new(2, u32::gt);
+    /// assert_eq!( topset.insert_tracked_age(7), None);
+    /// assert_eq!( topset.insert_tracked_age(8), None);
+    /// assert_eq!( topset.insert_tracked_age(1), Some((1,0)));
+    /// assert_eq!( topset.insert_tracked_age(9), Some((7,2)));
+    /// ```
+    #[inline]
+    pub fn insert_tracked_age(&mut self, x: X) -> Option<(X,u64)>
     {
-        let mut top = TopSet::<f32,_>::new(5, f32::lt);
-        top.extend(vec![81.5, 4.5, 4., 1., 45., 22., 11.]);
-        top.extend(vec![81.5, 4.5, 4., 1., 45., 22., 11.]);
-
-        assert_eq![ top.pop(), Some(4.5) ];
-        assert_eq![ top.pop(), Some(4.) ];
-        assert_eq![ top.pop(), Some(4.) ];
-        assert_eq![ top.pop(), Some(1.) ];
-        assert_eq![ top.pop(), Some(1.) ];
-        assert_eq![ top.pop(), None ];
+        if !self.tracked {
+            self.track_ages();
+        }
+        match self.push(x) {
+            Outcome::Added => None,
+            Outcome::Evicted(x, age) => Some((x, age)),
+            Outcome::Rejected(x) => Some((x, 0))
+        }
     }
 
-    #[test]
-    fn greatest_score()
+    /// Insert a new item and checks if the stored items changed.
+    ///
+    /// The returned option is the same as for [`Self::insert`].
+    /// The boolean is `true` if the new item was added (possibly by removing
+    /// another one) and `false` if it was rejected, leaving the top set unchanged.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::new(2, u32::gt);
+    /// assert_eq!( topset.insert_changed(7), (None, true));
+    /// assert_eq!( topset.insert_changed(8), (None, true));
+    /// assert_eq!( topset.insert_changed(9), (Some(7), true));
+    /// assert_eq!( topset.insert_changed(6), (Some(6), false));
+    /// ```
+    #[inline]
+    pub fn insert_changed(&mut self, x: X) -> (Option<X>, bool)
     {
-        assert_eq![
-            vec![81,5, 4,5,4,1,45,22,1,5,97,5,877,12,0]
-                .topset(5, u32::gt)
-                .into_iter()
-                .last(),
+        match self.push(x) {
+            Outcome::Added => (None, true),
+            Outcome::Evicted(x, _) => (Some(x), true),
+            Outcome::Rejected(x) => (Some(x), false)
+        }
+    }
+
+    /// Insert a new item built only if it would be kept.
+    ///
+    /// The item is not built yet: `will_beat` tells if it would beat a given stored
+    /// item (typically by comparing a cheap key). If the top set is full and
+    /// the item would not beat the lowest stored one, `make` is not called at all.
+    /// Otherwise, the item is built by `make` and inserted (see [`Self::insert_changed`]).
+    ///
+    /// The returned option is the removed item, if any, and the boolean is `true`
+    /// if the new item was built and stored.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::with_init(2, |a: &(u32,String), b: &(u32,String)| a.0 > b.0, vec![(7,"seven".to_string()), (9,"nine".to_string())]);
+    /// let (removed, kept) = topset.insert_with(|lowest| 3 > lowest.0, || (3, "three".to_string()));
+    /// assert_eq!( (removed, kept), (None, false) );
+    /// let (removed, kept) = topset.insert_with(|lowest| 8 > lowest.0, || (8, "eight".to_string()));
+    /// assert_eq!( (removed, kept), (Some((7,"seven".to_string())), true) );
+    /// ```
+    pub fn insert_with<W,F>(&mut self, will_beat: W, make: F) -> (Option<X>, bool)
+        where W: FnOnce(&X) -> bool, F: FnOnce() -> X
+    {
+        match self.peek() {
+            Some(lowest) if self.is_full() && !will_beat(lowest) => (None, false),
+            _ if self.limit() == 0 => (None, false),
+            _ => self.insert_changed(make())
+        }
+    }
+
+    /// Insert a new item and checks if it becomes the best one.
+    ///
+    /// The returned option is the same as for [`Self::insert`].
+    /// The boolean is `true` if, after insertion, the new item beats
+    /// all the other stored items (i.e. it is a new record).
+    /// If the item is rejected, or if it only ties with the current best one,
+    /// `false` is returned.
+    ///
+    /// Since the best item is one of the leaves of the heap, only the half
+    /// of the stored items is challenged.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::new(2, u32::gt);
+    /// assert_eq!( topset.insert_is_best(7), (None, true));
+    /// assert_eq!( topset.insert_is_best(5), (None, false));
+    /// assert_eq!( topset.insert_is_best(9), (Some(5), true));
+    /// assert_eq!( topset.insert_is_best(6), (Some(6), false));
+    /// ```
+    pub fn insert_is_best(&mut self, x: X) -> (Option<X>, bool)
+    {
+        let best = self.is_candidate(&x)
+            && self.heap[self.heap.len()/2..].iter().all(|e| self.beat(&x, e));
+        (self.insert(x), best)
+    }
+
+    /// Inserts all the items of an iterator and gets the removed ones.
+    ///
+    /// Each item is inserted as with [`Self::insert`] and all the removed items
+    /// (evicted stored items as well as rejected new items) are returned,
+    /// in an unspecified order. With a zero capacity, all the items are returned.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::new(2, u32::gt);
+    /// let mut removed = topset.insert_all(vec![7,5,6,9,4]);
+    /// removed.sort();
+    /// assert_eq!( removed, vec![4,5,6]);
+    /// assert_eq!( topset.into_sorted_vec(), vec![7,9]);
+    /// ```
+    pub fn insert_all<I: IntoIterator<Item=X>>(&mut self, iter: I) -> Vec<X>
+    {
+        iter.into_iter().filter_map(|x| self.insert(x)).collect()
+    }
+
+    /// Inserts all the items of an iterator and counts the accepted ones.
+    ///
+    /// This behaves as [`Extend::extend`] but returns the number of items which
+    /// were actually added to the top set (possibly by removing a stored one).
+    /// The rejected items are not counted.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::new(2, u32::gt);
+    /// assert_eq!( topset.extend_counted(vec![7,5,6]), 3);
+    /// assert_eq!( topset.extend_counted(vec![9,4,2,3]), 1);
+    /// assert_eq!( topset.into_sorted_vec(), vec![7,9]);
+    /// ```
+    pub fn extend_counted<I: IntoIterator<Item=X>>(&mut self, iter: I) -> usize
+    {
+        iter.into_iter()
+            .map(|x| self.push(x))
+            .filter(|outcome| !matches!(outcome, Outcome::Rejected(_)))
+            .count()
+    }
+
+    /// Expands an item into several candidates and inserts each of them.
+    ///
+    /// The item `x` is decomposed by `expand` and every sub-item is inserted
+    /// (see [`Self::insert`]). All the removed items (evicted stored items as well
+    /// as rejected sub-items) are returned.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::with_init(3, u32::gt, vec![4,5,6]);
+    /// let removed = topset.insert_expanded(70, |x| vec![x/10, x/7, x/5]);
+    /// assert_eq!( removed, vec![4,5,6]);
+    /// assert_eq!( topset.into_sorted_vec(), vec![7,10,14]);
+    /// ```
+    pub fn insert_expanded<I,F>(&mut self, x: X, expand: F) -> Vec<X>
+        where I: IntoIterator<Item=X>, F: Fn(X) -> I
+    {
+        self.insert_all(expand(x))
+    }
+
+    /// Converts this topset into a sorted iterator
+    ///
+    /// Notice that the _lowest_ item of the top set is the
+    /// first one. The _greatest_ item is the last one.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// // this topset contains { 7, 9 }
+    /// let topset = TopSet::with_init(2, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// let mut iter = topset.into_iter_sorted();
+    /// assert_eq!( iter.next(), Some(7));
+    /// assert_eq!( iter.next(), Some(9));
+    /// assert_eq!( iter.next(), None);
+    /// ```
+    ///
+    /// The iterator could also be consumed from the _greatest_ item
+    /// (each step scans the leaves of the heap so it costs `O(n)`).
+    /// ```
+    /// # use topset::TopSet;
+    /// let topset = TopSet::with_init(3, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// assert_eq!( topset.into_iter_sorted().rev().collect::<Vec<_>>(), vec![9,7,6]);
+    /// ```
+    #[inline]
+    pub fn into_iter_sorted(self) -> crate::iter::IntoIterSorted<X,C> {
+        self.into()
+    }
+
+    /// Gets an iterator over the items from the greatest one to the lowest one.
+    ///
+    /// The items are rearranged once (in linear time) with the reversed challenge,
+    /// and then each step costs `O(log n)`: taking only the first items is cheap
+    /// and does not sort the whole top set (contrary to reversing [`Self::into_sorted_vec`]).
+    /// The items come in the reversed order of [`Self::into_iter_sorted`].
+    /// The tied items are also reversed if the ties are broken (see [`Self::new_deterministic`]
+    /// and [`Self::new_fifo`]); otherwise, their relative order is unspecified.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let topset = TopSet::with_init(3, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// let mut iter = topset.into_iter_desc();
+    /// assert_eq!( iter.len(), 3);
+    /// assert_eq!( iter.next(), Some(9));
+    /// assert_eq!( iter.next(), Some(7));
+    /// assert_eq!( iter.len(), 1);
+    /// ```
+    pub fn into_iter_desc(mut self) -> crate::iter::IntoIterSorted<X, impl Fn(&X,&X) -> bool> {
+        self.ties = self.ties.opposite();
+        self.reversed().into_iter_sorted()
+    }
+
+    /// Returns the topset in a sorted vector.
+    ///
+    /// The first element of the vector is the _lowest_ item of the top set
+    /// and the last one is the _greatest_ one. The order is only deduced
+    /// from the challenge (the items are not required to be comparable).
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// // this topset contains { 7, 9 }
+    /// let topset = TopSet::with_init(3, u32::gt, vec![1,2,7,4,7,5,6,9,4,2,3] );
+    /// assert_eq!( topset.into_sorted_vec(), vec![7,7,9]);
+    /// ```
+    pub fn into_sorted_vec(mut self) -> Vec<X>
+    {
+        if self.ties != Ties::Unbroken {
+            let ties = self.ties;
+            let mut items: Vec<_> = self.stamps.into_iter().zip(self.heap).collect();
+            items.sort_unstable_by(|(i,a),(j,b)| duel_ordering(&self.beat, a, b).then(ties.ordering(*i, *j)));
+            return items.into_iter().map(|(_,x)| x).collect();
+        }
+        self.heap.sort_unstable_by(|a,b| duel_ordering(&self.beat, a, b));
+        self.heap
+    }
+
+    /// Returns the topset in a vector sorted by an explicit comparator.
+    ///
+    /// The items are selected by the challenge of the top set but are presented
+    /// in the order given by `cmp`, which is independent of the challenge.
+    /// So, the presentation order may not match the selection order.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// // select the 3 greatest but sort them by their last digit
+    /// let topset = TopSet::with_init(3, u32::gt, vec![17,25,6,39,4,12,3] );
+    /// assert_eq!( topset.into_sorted_vec_with(|a,b| (a%10).cmp(&(b%10))), vec![25,17,39]);
+    /// ```
+    pub fn into_sorted_vec_with<F>(mut self, cmp: F) -> Vec<X>
+        where F: Fn(&X,&X) -> Ordering
+    {
+        self.heap.sort_by(cmp);
+        self.heap
+    }
+
+    /// Returns the topset in a sorted double-ended queue.
+    ///
+    /// As for [`Self::into_sorted_vec`], the front of the queue is the _lowest_ item
+    /// of the top set and the back is the _greatest_ one, so both extremes could be
+    /// consumed in constant time.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let topset = TopSet::with_init(3, u32::gt, vec![1,2,7,4,8,5,6,9,4,2,3] );
+    /// let mut deque = topset.into_sorted_deque();
+    /// assert_eq!( deque.pop_back(), Some(9));
+    /// assert_eq!( deque.pop_front(), Some(7));
+    /// assert_eq!( deque.pop_back(), Some(8));
+    /// assert!( deque.is_empty() );
+    /// ```
+    #[inline]
+    pub fn into_sorted_deque(self) -> VecDeque<X>
+    {
+        self.into_sorted_vec().into()
+    }
+
+    /// Clears the top set, returning an iterator over the removed elements in sorted order.
+    ///
+    /// The items are popped one by one, from the _lowest_ to the _greatest_.
+    /// If the iterator is dropped before being fully consumed, the remaining
+    /// elements are popped and dropped in order, so the top set is always left empty.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::with_init(3, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// assert_eq!( topset.drain_sorted().collect::<Vec<_>>(), vec![6,7,9]);
+    /// assert!( topset.is_empty() );
+    /// ```
+    #[inline]
+    pub fn drain_sorted(&mut self) -> crate::iter::DrainSorted<'_,X,C> {
+        self.into()
+    }
+
+    /// Clears the binary heap, returning an iterator over the removed elements in arbitrary order.
+    /// If the iterator is dropped before being fully consumed, it drops the remaining elements in arbitrary order.
+    ///
+    /// The returned iterator keeps a mutable borrow on the heap to optimize its implementation.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::with_init(4, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// let _ = topset.drain();
+    /// assert! (topset.is_empty());
+    /// ```
+    #[inline]
+    pub fn drain(&mut self) -> alloc::vec::Drain<'_, X> {
+        self.stamps.clear();
+        self.dirty = false;
+        self.heap.drain(..)
+    }
+
+    /// Resize the top set
+    ///
+    /// If the size decreases, then the lowest items are removed.
+    /// If the size increases, nothing else happens but there is still more room
+    /// for next insertions.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::with_init(4, u32::gt, vec![7,5,6,9,4,2,3] );
+    ///
+    /// // the topset contains { 7, 5, 6, 9 }
+    /// assert_eq! (topset.peek(), Some(&5));
+    ///
+    /// topset.resize(2);
+    /// // the topset contains { 7, 9 }
+    /// assert_eq! (topset.peek(), Some(&7));
+    ///
+    /// // try to add 1 but no more room left
+    /// assert_eq!( topset.insert(1), Some(1) );
+    ///
+    /// topset.resize(3); // grows by one
+    /// assert_eq!( topset.insert(1), None ); // one room left
+    /// assert_eq!( topset.insert(2), Some(1) ); // but now, is full
+    /// // at this point, the topset contains { 7, 9, 2 }
+    /// ```
+    pub fn resize(&mut self, n: usize)
+    {
+        if self.count < n {
+            self.heap.reserve(n - self.count);
+        } else {
+            while self.heap.len() > n {
+                self.pop();
+            }
+        }
+        self.count = n;
+    }
+
+    /// Reserves storage for at least `additional` more items.
+    ///
+    /// Only the allocated storage is changed, not the capacity of the top set
+    /// (see [`Self::capacity`]): no more than `capacity()` items are stored anyway.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::<u32,_>::new(2, u32::gt);
+    /// topset.reserve(100);
+    /// assert_eq!( topset.capacity(), 2 );
+    /// ```
+    pub fn reserve(&mut self, additional: usize)
+    {
+        self.heap.reserve(additional);
+        if self.stamped() {
+            self.stamps.reserve(additional);
+        }
+    }
+
+    /// Shrinks the allocated storage as much as possible.
+    ///
+    /// This is useful after [`Self::resize`] to a lower capacity.
+    /// As for [`Self::reserve`], the capacity of the top set is not changed.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::with_init(1000, u32::gt, 0..1000 );
+    /// topset.resize(2);
+    /// topset.shrink_to_fit();
+    /// assert_eq!( topset.capacity(), 2 );
+    /// ```
+    pub fn shrink_to_fit(&mut self)
+    {
+        self.heap.shrink_to_fit();
+        self.stamps.shrink_to_fit();
+    }
+
+    /// Lowers temporarily the number of stored items
+    ///
+    /// The number of stored items is limited to the minimum of the capacity
+    /// (see [`Self::capacity`], which is left unchanged) and this effective capacity.
+    /// If there are more stored items, the lowest ones are removed immediately.
+    ///
+    /// This is intended to throttle the memory under pressure, at the price
+    /// of accuracy: the removed items are lost even if the effective capacity
+    /// is raised later (by setting it to `usize::MAX` for instance).
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::with_init(4, u32::gt, vec![7,5,6,9,4,2,3] );
+    ///
+    /// topset.set_effective_capacity(2);
+    /// assert_eq!( topset.capacity(), 4 );
+    /// assert_eq!( topset.effective_capacity(), 2 );
+    /// assert_eq!( topset.peek(), Some(&7) );
+    /// assert_eq!( topset.insert(8), Some(7) );
+    ///
+    /// topset.set_effective_capacity(usize::MAX);
+    /// assert_eq!( topset.effective_capacity(), 4 );
+    /// assert_eq!( topset.insert(1), None );
+    /// ```
+    pub fn set_effective_capacity(&mut self, n: usize)
+    {
+        self.effective = n;
+        while self.heap.len() > self.limit() {
+            self.pop();
+        }
+    }
+
+    /// Get the effective capacity of this top set
+    ///
+    /// This is the actual limit of the number of stored items, i.e. the minimum
+    /// of the capacity and of the effective capacity (see [`Self::set_effective_capacity`]).
+    #[inline]
+    pub fn effective_capacity(&self) -> usize { self.limit() }
+
+    /// Pop the lowest item of the top set
+    ///
+    /// Remove and return the _lowest_ item of the top set.
+    /// After this call, there is one more room for a item.
+    ///
+    /// This method is the only way to get the top elements
+    /// in a sorted way (from the lowest to the best).
+    /// Resize the top set
+    ///
+    /// If the size decreases, then the lowest items are removed.
+    /// If the size increases, nothing else happens but there is still more room
+    /// for next insertions.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::with_init(2, u32::gt, vec![7,5,6,9,4,2,3] );
+    ///
+    /// assert_eq! (topset.pop(), Some(7));
+    /// assert_eq! (topset.pop(), Some(9));
+    /// assert_eq! (topset.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<X>
+    {
+        debug_assert!(self.is_repaired(), "the top set was edited through as_mut_slice without calling repair");
+        match self.heap.len() {
+            0 => None,
+            1|2 => Some(self.swap_remove(0)),
+            _ => {
+                let pop = self.swap_remove(0);
+                self.percolate_down(0);
+                Some(pop)
+            }
+        }
+    }
+
+    /// Pop the `k` lowest items of the top set
+    ///
+    /// The removed items are returned in a sorted vector (the _lowest_ first).
+    /// If `k` is greater than the number of stored items, the top set is emptied.
+    ///
+    /// The capacity is not changed (see [`Self::resize`] to drop the lowest
+    /// items while reducing the capacity).
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::with_init(4, u32::gt, vec![7,5,6,9,4,2,3] );
+    ///
+    /// assert_eq! (topset.pop_n(2), vec![5,6]);
+    /// assert_eq! (topset.pop_n(5), vec![7,9]);
+    /// assert!( topset.is_empty() );
+    /// ```
+    pub fn pop_n(&mut self, k: usize) -> Vec<X>
+    {
+        let mut popped = Vec::with_capacity(k.min(self.heap.len()));
+        while popped.len() < k {
+            match self.pop() {
+                Some(x) => popped.push(x),
+                None => break
+            }
+        }
+        popped
+    }
+
+    /// Removes all the elements in the top set
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::with_init(2, u32::gt, vec![7,5,6,9,4,2,3] );
+    ///
+    /// assert_eq! (topset.len(), 2);
+    /// topset.clear();
+    /// assert_eq!( topset.len(), 0)
+    /// ```
+    #[inline]
+    pub fn clear(&mut self)
+    {
+        self.heap.clear();
+        self.stamps.clear();
+        self.dirty = false;
+    }
+
+    /// Removes an item from the top set
+    ///
+    /// The first stored item equal to `x` is removed, whatever its rank,
+    /// and the heap is restored in `O(log n)`. Returns `true` if an item
+    /// was found and removed.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::with_init(3, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// assert!( topset.remove(&7) );
+    /// assert!( ! topset.remove(&5) );
+    /// assert_eq!( topset.into_sorted_vec(), vec![6,9]);
+    /// ```
+    pub fn remove(&mut self, x: &X) -> bool
+        where X: PartialEq
+    {
+        let Some(i) = self.heap.iter().position(|e| e == x) else {
+            return false;
+        };
+        // the last item takes the place of the removed one and goes up or down
+        self.swap_remove(i);
+        if i < self.heap.len() {
+            if i > 0 && self.duel((i-1)/2, i) {
+                self.percolate_up(i);
+            } else {
+                self.percolate_down(i);
+            }
+        }
+        true
+    }
+
+    /// Retains only the items specified by the predicate
+    ///
+    /// All the items for which `f` returns `false` are removed, whatever
+    /// their rank. The heap is then rebuilt once, in linear time.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::with_init(4, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// topset.retain(|x| x % 2 == 1);
+    /// assert_eq!( topset.peek(), Some(&5) );
+    /// assert_eq!( topset.into_sorted_vec(), vec![5,7,9]);
+    /// ```
+    pub fn retain<F: FnMut(&X) -> bool>(&mut self, mut f: F)
+    {
+        let mut i = 0;
+        while i < self.heap.len() {
+            if f(&self.heap[i]) {
+                i += 1;
+            } else {
+                self.swap_remove(i);
+            }
+        }
+        self.heapify();
+        self.dirty = false;
+    }
+
+    /// Applies a decay to all the stored items
+    ///
+    /// Every stored item is modified by `decay` (e.g. its score is lowered)
+    /// and then the heap is rebuilt so that the ranking reflects the aged items.
+    /// Called periodically, it lets fresh items displace stale ones.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::with_init(2, f64::gt, vec![100., 80.] );
+    /// topset.apply_decay(|x| *x *= 0.5);
+    /// assert_eq!( topset.insert(60.), Some(40.) );
+    /// assert_eq!( topset.into_sorted_vec(), vec![50., 60.] );
+    /// ```
+    pub fn apply_decay<F: Fn(&mut X)>(&mut self, decay: F)
+    {
+        self.heap.iter_mut().for_each(decay);
+        self.heapify();
+        self.dirty = false;
+    }
+
+    /// Gives a mutable access to all the stored items (**not** sorted).
+    ///
+    /// This is intended for bulk in-place edits of the items (e.g. updating
+    /// all the scores at once). Such edits may break the internal ordering,
+    /// so the caller **must** call [`Self::repair`] before any other operation
+    /// on the top set (this is checked in debug builds).
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::with_init(2, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// topset.as_mut_slice().iter_mut().for_each(|x| *x = 100 - *x);
+    /// topset.repair();
+    /// assert_eq!( topset.into_sorted_vec(), vec![91,93]);
+    /// ```
+    pub fn as_mut_slice(&mut self) -> &mut [X]
+    {
+        self.dirty = true;
+        &mut self.heap
+    }
+
+    /// Restores the top set after edits through [`Self::as_mut_slice`].
+    ///
+    /// The heap is rebuilt (in linear time) and, if there are more items than
+    /// the capacity allows, the lowest ones are dropped.
+    pub fn repair(&mut self)
+    {
+        self.heapify();
+        self.dirty = false;
+        while self.heap.len() > self.limit() {
+            self.pop();
+        }
+    }
+
+    /// Replaces all the items by the top ones of an iterator
+    ///
+    /// The top set is cleared, keeping its capacity, its challenge and its
+    /// allocated storage, and then it is filled with the items of `iter`.
+    /// The heap is built once from the first items (in linear time) before
+    /// streaming the remaining ones.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::with_init(2, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// topset.refill(vec![1,3,2]);
+    /// assert_eq!( topset.into_sorted_vec(), vec![2,3]);
+    /// ```
+    pub fn refill<I: IntoIterator<Item=X>>(&mut self, iter: I)
+    {
+        self.clear();
+        let mut iter = iter.into_iter();
+        let limit = self.limit();
+        self.heap.extend(iter.by_ref().take(limit));
+        let seq = self.seq + self.heap.len() as u64;
+        if self.stamped() {
+            self.stamps.extend(self.seq..seq);
+        }
+        self.seq = seq;
+        self.heapify();
+        self.extend(iter);
+    }
+
+    /// Checks if an element beats the other.
+    ///
+    /// It does not related to the current elements in the topset but
+    /// refers only to the comparing function (the _beat_).
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::with_init(2, u32::gt, vec![7,5,6,9,4,2,3] );
+    ///
+    /// assert! ( topset.beat(&4, &3));
+    /// assert! ( ! topset.beat(&4, &7));
+    /// ```
+    #[inline] pub fn beat(&self, a:&X, b:&X) -> bool { (self.beat)(a,b) }
+
+    /// Checks that the items are ordered as a heap.
+    ///
+    /// No stored item beats one of its children, so the root is the lowest
+    /// item according to the challenge. Only `beat` is used (no equality needed).
+    /// This is intended for tests and `debug_assert!`, for instance to catch
+    /// a non-transitive challenge or an item edited in place.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::with_init(4, u32::gt, vec![7,5,6,9,4,2,3] );
+    /// assert!( topset.is_valid_heap() );
+    /// topset.as_mut_slice()[0] = 100;
+    /// assert!( !topset.is_valid_heap() );
+    /// topset.repair();
+    /// assert!( topset.is_valid_heap() );
+    /// ```
+    pub fn is_valid_heap(&self) -> bool
+    {
+        (1..self.heap.len()).all(|i| !(self.beat)(&self.heap[(i-1)/2], &self.heap[i]))
+    }
+
+    /// Checks that the challenge behaves as a strict order on the stored items.
+    ///
+    /// A few stored items (at most 16, evenly spread in the buffer) are sampled
+    /// and, for all of them, it is checked that:
+    /// * no item beats itself,
+    /// * two items do not beat each other,
+    /// * if `a` beats `b` and `b` beats `c`, then `a` beats `c`.
+    ///
+    /// This is a debugging aid, never called implicitly. It catches the classical
+    /// mistakes such as `f64::ge` (an item beats itself) or a `partial_cmp` based
+    /// challenge which mishandles `NaN`. The checks are only done in debug builds:
+    /// in release builds, this method does nothing.
+    ///
+    /// # Panics
+    /// In debug builds, if one of these properties is violated, with a message telling which one.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let topset = TopSet::with_init(4, f64::gt, vec![7.,5.,6.,9.] );
+    /// topset.check_comparator_sanity();
+    /// ```
+    ///
+    /// In debug builds, this panics since 9 beats itself:
+    /// ```no_run
+    /// # use topset::TopSet;
+    /// let topset = TopSet::with_init(4, f64::ge, vec![7.,5.,6.,9.] );
+    /// topset.check_comparator_sanity();
+    /// ```
+    pub fn check_comparator_sanity(&self)
+    {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        const SAMPLES: usize = 16;
+        let step = self.heap.len().div_ceil(SAMPLES).max(1);
+        let sample = self.heap.iter().step_by(step).collect::<Vec<_>>();
+        for (i, a) in sample.iter().enumerate() {
+            assert!( !(self.beat)(a, a), "the challenge is not irreflexive: an item (#{i} of the sample) beats itself" );
+            for (j, b) in sample.iter().enumerate() {
+                if !(self.beat)(a, b) {
+                    continue;
+                }
+                assert!( !(self.beat)(b, a), "the challenge is not antisymmetric: the items #{i} and #{j} of the sample beat each other" );
+                for (k, c) in sample.iter().enumerate() {
+                    assert!( !(self.beat)(b, c) || (self.beat)(a, c),
+                             "the challenge is not transitive: #{i} beats #{j} which beats #{k}, but #{i} does not beat #{k} (in the sample)" );
+                }
+            }
+        }
+    }
+
+    /// Absorbs another top set.
+    ///
+    /// The items of both top sets are gathered and only the greatest ones
+    /// are stored, up to the capacity of this top set (whatever the capacity
+    /// of `other`). The selection is done at once, in linear time,
+    /// rather than by inserting the items of `other` one by one.
+    ///
+    /// Both top sets are expected to use the same challenge.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut shard1 = TopSet::with_init(3, u32::gt, vec![7,5,6,9]);
+    /// let shard2 = TopSet::with_init(3, u32::gt, vec![4,8,2,3]);
+    /// shard1.absorb(shard2);
+    /// assert_eq!( shard1.into_sorted_vec(), vec![7,8,9]);
+    /// ```
+    pub fn absorb(&mut self, other: TopSet<X,C>)
+    {
+        let mut data = mem::take(&mut self.heap);
+        data.extend(other.heap);
+        self.reset(data);
+    }
+
+    /// Moves all the items of another top set into this one.
+    ///
+    /// The items of `other` are inserted one by one (see [`Self::insert`]),
+    /// so only the greatest ones are kept up to the capacity of this top set.
+    /// Unlike [`Self::absorb`], `other` is not consumed: it is left empty
+    /// but keeps its capacity and its challenge.
+    ///
+    /// Both top sets are expected to use the same challenge.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut shard1 = TopSet::with_init(3, u32::gt, vec![7,5,6,9]);
+    /// let mut shard2 = TopSet::with_init(3, u32::gt, vec![4,8,2,3]);
+    /// shard1.append(&mut shard2);
+    /// assert!( shard2.is_empty() );
+    /// assert_eq!( shard1.into_sorted_vec(), vec![7,8,9]);
+    /// ```
+    pub fn append(&mut self, other: &mut TopSet<X,C>)
+    {
+        self.extend(other.drain());
+    }
+
+    /// Merges another top set, keeping only the best item per key.
+    ///
+    /// All the items of `other` are merged into this top set but, among the items
+    /// sharing the same key (whatever the set they come from), only the one which
+    /// wins the duels is kept. Then, only the greatest items are stored, up to the
+    /// capacity of this top set.
+    ///
+    /// Both top sets are expected to use the same challenge.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let beat = |a: &(char,u32), b: &(char,u32)| a.1 > b.1;
+    /// let mut shard1 = TopSet::with_init(3, beat, vec![('a',5), ('b',9), ('c',1)]);
+    /// let shard2 = TopSet::with_init(3, beat, vec![('a',8), ('b',2), ('d',4)]);
+    /// shard1.merge_dedup_by_key(shard2, |x| x.0);
+    /// assert_eq!( shard1.into_sorted_vec(), vec![('d',4), ('a',8), ('b',9)]);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn merge_dedup_by_key<K,F>(&mut self, other: TopSet<X,C>, key: F)
+        where K: Eq + Hash, F: Fn(&X) -> K
+    {
+        let mut best = HashMap::with_capacity(self.heap.len() + other.heap.len());
+        for x in mem::take(&mut self.heap).into_iter().chain(other.heap) {
+            match best.entry(key(&x)) {
+                Entry::Occupied(mut e) => if self.beat(&x, e.get()) { e.insert(x); },
+                Entry::Vacant(e) => { e.insert(x); }
+            }
+        }
+        self.reset(best.into_values().collect());
+    }
+
+    /// Refines the challenge with a tie-breaking one.
+    ///
+    /// The stored items are kept but the new challenge uses the current one
+    /// first and `extra_beat` only when neither of two items beats the other.
+    /// So, the order changes only among the items which were tied.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let topset = TopSet::with_init(3, |a: &(u32,char), b| a.0 > b.0, vec![(1,'a'),(2,'b'),(2,'c'),(0,'d')]);
+    /// let topset = topset.refine(|a, b| a.1 < b.1);
+    /// assert_eq!( topset.into_iter_sorted().collect::<Vec<_>>(), vec![(1,'a'),(2,'c'),(2,'b')]);
+    /// ```
+    pub fn refine<F>(self, extra_beat: F) -> TopSet<X, impl Fn(&X,&X) -> bool>
+        where F: Fn(&X,&X) -> bool
+    {
+        self.map_beat(|beat| lexicographic(beat, extra_beat))
+    }
+
+    /// Reverses the challenge to select the opposite extreme.
+    ///
+    /// The new challenge is the current one with swapped arguments, so that
+    /// the next insertions select the lowest items instead of the greatest
+    /// ones (and conversely). The stored items are kept and re-arranged.
+    ///
+    /// Notice that the currently stored items were selected by the former
+    /// challenge, so they are the _opposite_ extreme: typically, this should
+    /// be called on an empty top set (e.g. a fresh one or a cloned and cleared one).
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let greatest = TopSet::new(2, |a: &(char,u32), b: &(char,u32)| a.1 > b.1);
+    /// let mut lowest = greatest.clone().reversed();
+    /// lowest.extend(vec![('a',5), ('b',1), ('c',9), ('d',3)]);
+    /// assert_eq!( lowest.into_sorted_vec(), vec![('d',3), ('b',1)]);
+    /// ```
+    pub fn reversed(self) -> TopSet<X, impl Fn(&X,&X) -> bool>
+    {
+        self.map_beat(|beat| move |a: &X, b: &X| beat(b,a))
+    }
+
+    // internal stuff
+    // insert an item and tell what happened
+    fn push(&mut self, x: X) -> Outcome<X>
+    {
+        if !self.is_full() {
+            // some room left, so nothing to remove
+            self.heap.push(x);
+            if self.stamped() {
+                self.stamps.push(self.seq);
+            }
+            self.seq += 1;
+            self.percolate_up(self.heap.len()-1);
+            Outcome::Added
+        } else {
+            // SAFETY: if the heap is empty when self.limit() != 0, then we fall
+            // in the previous if condition (so, here, get_unchecked is safe)
+            if self.limit() != 0 && self.wins_as_newest(&x, unsafe { self.heap.get_unchecked(0) }) {
+                self.replace_lowest(x)
+            } else {
+                self.seq += 1;
+                Outcome::Rejected(x)
+            }
+        }
+    }
+
+    // internal stuff
+    // replace the lowest item by a new one (the top set should not be empty)
+    fn replace_lowest(&mut self, mut x: X) -> Outcome<X>
+    {
+        let stamp = self.seq;
+        self.seq += 1;
+        // put the greatest the deepest: the new one should be kept
+        mem::swap(&mut x, &mut self.heap[0]);
+        // the age is only known if the stamps are maintained
+        let age = if self.stamped() { stamp - mem::replace(&mut self.stamps[0], stamp) - 1 } else { 0 };
+        self.percolate_down(0);
+        Outcome::Evicted(x, age)
+    }
+
+    // internal stuff
+    // checks if a new item beats a stored one (the new one is the most recent for the ties)
+    #[inline]
+    fn wins_as_newest(&self, x: &X, stored: &X) -> bool
+    {
+        self.beat(x, stored) || (self.ties == Ties::Newest && !self.beat(stored, x))
+    }
+
+    // internal stuff
+    // the stored items in a canonical order (independent of the heap layout)
+    fn canonical(&self) -> Vec<&X>
+        where X: Ord
+    {
+        let mut items = self.heap.iter().collect::<Vec<_>>();
+        items.sort_unstable();
+        items
+    }
+
+    // internal stuff
+    // a sorted copy of the items (the lowest first)
+    pub(crate) fn to_sorted_vec(&self) -> Vec<X>
+        where X: Clone
+    {
+        self.iter_sorted().cloned().collect()
+    }
+
+    /// Reconfigures the top set with a new capacity and a new challenge.
+    ///
+    /// The stored items are kept as candidates: only the `n` greatest ones
+    /// according to the new challenge `beat` are stored (in linear time).
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let topset = TopSet::with_init(4, u32::gt, vec![17,25,6,39,4,12,3] );
+    /// // the topset contains { 17, 25, 39, 12 }
+    /// let topset = topset.reconfigure(2, |a,b| a%10 > b%10);
+    /// assert_eq!( topset.into_sorted_vec(), vec![17,39]);
+    /// ```
+    pub fn reconfigure<C2>(self, n: usize, beat: C2) -> TopSet<X,C2>
+        where C2: Fn(&X,&X) -> bool
+    {
+        let mut top = TopSet {
+            heap: Vec::new(),
+            stamps: Vec::new(),
+            seq: self.seq,
+            tracked: self.tracked,
+            count: n,
+            effective: self.effective,
+            ties: self.ties,
+            dirty: false,
+            beat
+        };
+        top.reset(self.heap);
+        top
+    }
+
+    /// Replaces the challenge, keeping all the stored items.
+    ///
+    /// The heap is rebuilt (in linear time) according to the new challenge `beat`,
+    /// so the lowest item is the one of the new ranking. The stored items are only
+    /// re-ranked: they were selected by the former challenge, so they are not
+    /// necessarily the top items of the stream according to the new one.
+    /// See [`Self::reconfigure`] to also change the capacity.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let topset = TopSet::with_init(3, u32::gt, vec![17,25,6,39,4,12,3] );
+    /// // the topset contains { 17, 25, 39 }, now ranked by the units
+    /// let mut topset = topset.set_comparator(|a,b| a%10 > b%10);
+    /// assert_eq!( topset.peek(), Some(&25) );
+    /// // but 12 was already dropped by the former challenge
+    /// assert_eq!( topset.insert(12), Some(12) );
+    /// ```
+    pub fn set_comparator<C2>(self, beat: C2) -> TopSet<X,C2>
+        where C2: Fn(&X,&X) -> bool
+    {
+        self.map_beat(|_| beat)
+    }
+
+    /// Clones the top set with another challenge.
+    ///
+    /// The derived [`Clone`] requires the challenge to be [`Clone`] too, which is
+    /// not the case of the closures capturing a non-cloneable state (or of a
+    /// boxed challenge, see [`BoxedTopSet`]). This method only clones the items
+    /// (and the capacity) and takes a fresh challenge `beat`, which is expected to
+    /// be equivalent to the current one (the heap is restored anyway, in linear time).
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::{BoxedTopSet, TopSet};
+    /// let mut topset: BoxedTopSet<u32> = TopSet::boxed(2, |a: &u32, b: &u32| a > b);
+    /// topset.extend(vec![7,5,6,9,4,2,3]);
+    /// let mut copy = topset.clone_with(u32::gt);
+    /// copy.insert(10);
+    /// assert_eq!( copy.into_sorted_vec(), vec![9,10]);
+    /// assert_eq!( topset.into_sorted_vec(), vec![7,9]);
+    /// ```
+    pub fn clone_with<C2>(&self, beat: C2) -> TopSet<X,C2>
+        where X: Clone, C2: Fn(&X,&X) -> bool
+    {
+        let mut top = TopSet {
+            heap: self.heap.clone(),
+            stamps: self.stamps.clone(),
+            seq: self.seq,
+            tracked: self.tracked,
+            count: self.count,
+            effective: self.effective,
+            ties: self.ties,
+            dirty: self.dirty,
+            beat
+        };
+        if !top.dirty {
+            top.heapify();
+        }
+        top
+    }
+
+    // internal stuff
+    // an empty top set with the same settings (capacity, effective capacity and ties)
+    pub(crate) fn empty_like(&self) -> Self
+        where C: Clone
+    {
+        TopSet {
+            heap: Vec::with_capacity(self.heap.capacity()),
+            stamps: Vec::with_capacity(self.stamps.capacity()),
+            seq: 0,
+            tracked: self.tracked,
+            count: self.count,
+            effective: self.effective,
+            ties: self.ties,
+            dirty: false,
+            beat: self.beat.clone()
+        }
+    }
+
+    // internal stuff
+    // the index of the best item (which is one of the leaves, the ties being broken as in the heap)
+    fn best_index(&self) -> Option<usize>
+    {
+        (self.heap.len()/2..self.heap.len())
+            .reduce(|best, i| if self.duel(i, best) { i } else { best })
+    }
+
+    // internal stuff
+    // removes the best item (since it is a leaf, the replacing item could only go up)
+    pub(crate) fn pop_best(&mut self) -> Option<X>
+    {
+        let i = self.best_index()?;
+        let best = self.swap_remove(i);
+        if i < self.heap.len() {
+            self.percolate_up(i);
+        }
+        Some(best)
+    }
+
+    // internal stuff
+    // checks that no unrepaired edits broke the heap (only used in debug builds)
+    fn is_repaired(&self) -> bool
+    {
+        !self.dirty || (1..self.heap.len()).all(|i| !self.duel((i-1)/2, i))
+    }
+
+    // internal stuff
+    // a top set with the default settings, using the empty vector `heap` as storage
+    pub(crate) fn with_storage(n: usize, beat: C, heap: Vec<X>) -> Self
+    {
+        debug_assert!(heap.is_empty());
+        Self {
+            heap,
+            stamps: Vec::new(),
+            seq: 0,
+            tracked: false,
+            count: n,
+            effective: usize::MAX,
+            ties: Ties::Unbroken,
+            dirty: false,
+            beat
+        }
+    }
+
+    // internal stuff
+    // the actual maximum number of stored items
+    #[inline]
+    fn limit(&self) -> usize { self.count.min(self.effective) }
+
+    // internal stuff
+    // checks if the insertion stamps are maintained (only to break the ties or to track the ages)
+    #[inline]
+    fn stamped(&self) -> bool { self.tracked || self.ties != Ties::Unbroken }
+
+    // internal stuff
+    // the order of two stored items given their insertion stamps (if the ties are broken)
+    #[inline]
+    fn tie_ordering(&self, i: usize, j: usize) -> Ordering
+    {
+        if self.ties == Ties::Unbroken {
+            Ordering::Equal
+        } else {
+            self.ties.ordering(self.stamps[i], self.stamps[j])
+        }
+    }
+
+    // internal stuff
+    // removes an item (and its stamp) without restoring the heap
+    #[inline]
+    fn swap_remove(&mut self, i: usize) -> X
+    {
+        if self.stamped() {
+            self.stamps.swap_remove(i);
+        }
+        self.heap.swap_remove(i)
+    }
+
+    // internal stuff
+    // starts tracking the ages (the stored items are considered as inserted by the last insertion)
+    fn track_ages(&mut self)
+    {
+        if !self.stamped() {
+            self.stamps.resize(self.heap.len(), self.seq.saturating_sub(1));
+        }
+        self.tracked = true;
+    }
+
+    // internal stuff
+    // replace all the items by the top ones of an arbitrary vector (in linear time)
+    fn reset(&mut self, mut data: Vec<X>)
+    {
+        if self.limit() == 0 {
+            data.clear();
+        } else if data.len() > self.limit() {
+            // moves the lowest items at the beginning and drops them
+            let excess = data.len() - self.limit();
+            data.select_nth_unstable_by(excess, |a,b| duel_ordering(&self.beat, a, b));
+            data.drain(..excess);
+        }
+        let seq = self.seq + data.len() as u64;
+        self.stamps = if self.stamped() { (self.seq..seq).collect() } else { Vec::new() };
+        self.seq = seq;
+        self.heap = data;
+        self.heapify();
+        self.dirty = false;
+    }
+
+    // internal stuff
+    // install a new challenge deduced from the current one and restore the heap
+    fn map_beat<C2, F>(self, f: F) -> TopSet<X,C2>
+        where C2: Fn(&X,&X) -> bool, F: FnOnce(C) -> C2
+    {
+        let mut top = TopSet {
+            heap: self.heap,
+            stamps: self.stamps,
+            seq: self.seq,
+            tracked: self.tracked,
+            count: self.count,
+            effective: self.effective,
+            ties: self.ties,
+            dirty: false,
+            beat: f(self.beat)
+        };
+        top.heapify();
+        top
+    }
+}
+
+
+// internal stuff
+// a buffer ordered as a heap with the lowest item at the root
+// (shared by all the top set flavours)
+pub(crate) trait HeapBuffer
+{
+    // number of children of each node (a binary heap by default)
+    const ARITY: usize = 2;
+
+    // number of items in the buffer
+    fn size(&self) -> usize;
+
+    // checks if the item at `i` beats the item at `j`
+    fn duel(&self, i: usize, j: usize) -> bool;
+
+    // exchanges the items at `i` and `j`
+    fn exchange(&mut self, i: usize, j: usize);
+
+    // move i up (to the best)
+    fn percolate_up(&mut self, mut i: usize)
+    {
+        while i > 0 { // so has a parent (not root)
+            let parent = (i-1)/Self::ARITY;
+            // put the greatest the deepest
+            if self.duel(parent, i) {
+                self.exchange(parent, i);
+                i = parent;
+            } else {
+                break;
+            }
+        }
+    }
+
+    // restore the heap property of the whole buffer in linear time
+    fn heapify(&mut self)
+    {
+        // only the nodes with at least one child
+        for i in (0..(self.size() + Self::ARITY - 2)/Self::ARITY).rev() {
+            self.percolate_down(i);
+        }
+    }
+
+    // move i as deep as possible
+    fn percolate_down(&mut self, mut i: usize)
+    {
+        let size = self.size();
+        loop {
+            let first = Self::ARITY*i+1;
+            if first >= size {
+                // end of heap
+                break;
+            }
+            // to put the greatest the deepest -> select the lowest child
+            let mut child = first;
+            for other in first+1..(first + Self::ARITY).min(size) {
+                if self.duel(child, other) {
+                    child = other;
+                }
+            }
+            // put the greatest the deepest
+            if self.duel(i, child) {
+                self.exchange(i, child);
+                i = child;
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+// internal stuff
+// how the ties (items which do not beat each other) are broken
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Ties
+{
+    Unbroken,
+    Oldest, // the first inserted item wins
+    Newest // the last inserted item wins
+}
+
+impl Ties
+{
+    // the order of two tied items given their insertion stamps (the winner is the greatest)
+    #[inline]
+    fn ordering(self, a: u64, b: u64) -> Ordering
+    {
+        match self {
+            Ties::Unbroken => Ordering::Equal,
+            Ties::Oldest => b.cmp(&a),
+            Ties::Newest => a.cmp(&b)
+        }
+    }
+
+    // the policy which breaks the ties the other way round
+    #[inline]
+    fn opposite(self) -> Self
+    {
+        match self {
+            Ties::Unbroken => Ties::Unbroken,
+            Ties::Oldest => Ties::Newest,
+            Ties::Newest => Ties::Oldest
+        }
+    }
+}
+
+// internal stuff
+// the outcome of an insertion
+enum Outcome<X>
+{
+    Added,
+    Evicted(X, u64), // the removed item and the number of insertions it survived
+    Rejected(X)
+}
+
+// internal stuff
+// the ordering deduced from a challenge (the winner is the greatest)
+pub(crate) fn duel_ordering<X>(beat: impl Fn(&X,&X) -> bool, a: &X, b: &X) -> Ordering
+{
+    if beat(a,b) {
+        Ordering::Greater
+    } else if beat(b,a) {
+        Ordering::Less
+    } else {
+        Ordering::Equal
+    }
+}
+
+impl<X,C> HeapBuffer for TopSet<X,C>
+    where C: Fn(&X,&X) -> bool
+{
+    #[inline] fn size(&self) -> usize { self.heap.len() }
+    #[inline] fn duel(&self, i: usize, j: usize) -> bool {
+        (self.beat)(&self.heap[i], &self.heap[j])
+            || (self.tie_ordering(i, j).is_gt() && !(self.beat)(&self.heap[j], &self.heap[i]))
+    }
+    #[inline] fn exchange(&mut self, i: usize, j: usize) {
+        self.heap.swap(i, j);
+        if self.stamped() {
+            self.stamps.swap(i, j);
+        }
+    }
+}
+
+
+/// A mutable access to the lowest item of a top set.
+///
+/// It is created by [`TopSet::peek_mut`]. When it is dropped,
+/// the modified item is moved at its right place in the top set.
+pub struct PeekMut<'a,X,C>
+    where C: Fn(&X,&X) -> bool
+{
+    top: &'a mut TopSet<X,C>
+}
+
+impl<X,C> Deref for PeekMut<'_,X,C>
+    where C: Fn(&X,&X) -> bool
+{
+    type Target = X;
+    #[inline] fn deref(&self) -> &X { &self.top.heap[0] }
+}
+
+impl<X,C> DerefMut for PeekMut<'_,X,C>
+    where C: Fn(&X,&X) -> bool
+{
+    #[inline] fn deref_mut(&mut self) -> &mut X { &mut self.top.heap[0] }
+}
+
+impl<X,C> Drop for PeekMut<'_,X,C>
+    where C: Fn(&X,&X) -> bool
+{
+    #[inline] fn drop(&mut self) { self.top.percolate_down(0) }
+}
+
+/// A top set whose challenge is a boxed closure.
+///
+/// All the top sets of this type share the same concrete type whatever their
+/// challenge, so they could be stored together (e.g. when the ranking strategies
+/// are provided by plugins).
+pub type BoxedTopSet<X> = TopSet<X, Box<dyn Fn(&X,&X) -> bool + Send>>;
+
+impl<X> BoxedTopSet<X>
+{
+    /// Creates a new top set with a boxed selecting closure.
+    ///
+    /// The closure is not required to be `Clone`.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::{BoxedTopSet, TopSet};
+    /// let strategies: Vec<BoxedTopSet<u32>> = vec![
+    ///     TopSet::boxed(2, u32::gt),
+    ///     TopSet::boxed(2, |a: &u32, b: &u32| a % 10 > b % 10),
+    /// ];
+    /// ```
+    pub fn boxed<F>(n: usize, beat: F) -> Self
+        where F: Fn(&X,&X) -> bool + Send + 'static
+    {
+        Self::new(n, Box::new(beat))
+    }
+}
+
+impl<X> TopSet<X, fn(&X,&X) -> bool>
+{
+    /// Creates a new top set with a comparison function.
+    ///
+    /// The `n` greatest items according to `cmp` are kept: an item beats
+    /// another one if `cmp` returns [`Ordering::Greater`].
+    /// This allows to reuse an existing comparison such as [`Ord::cmp`].
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// // the longest words first, then the lexicographic order
+    /// let mut topset = TopSet::new_by(2, |a: &&str, b: &&str| a.len().cmp(&b.len()).then(a.cmp(b)));
+    /// topset.extend(vec!["kiwi", "fig", "plum", "pear"]);
+    /// assert_eq!( topset.into_sorted_vec(), vec!["pear", "plum"]);
+    /// ```
+    pub fn new_by<F>(n: usize, cmp: F) -> TopSet<X, impl Fn(&X,&X) -> bool>
+        where F: Fn(&X,&X) -> Ordering
+    {
+        TopSet::new(n, ordering_beat(cmp))
+    }
+
+    /// Creates a new top set with a primary challenge and a tie-breaking one.
+    ///
+    /// An item beats another one if it wins the `primary` challenge or, when
+    /// neither beats the other with `primary` (a tie), if it wins the `secondary`
+    /// one: this is the lexicographic order, `primary` taking precedence.
+    /// If both challenges are total orders (the ties of `primary` being equivalent
+    /// items), so is the composed one. See also [`TopSet::refine`] to add
+    /// a tie-break to an existing top set.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// // the highest scores first, then the alphabetical order of the names
+    /// let mut topset = TopSet::new_with_tiebreak(2, |a: &(u32,char), b| a.0 > b.0, |a, b| a.1 < b.1);
+    /// topset.extend(vec![(3,'d'), (1,'a'), (3,'b'), (3,'c')]);
+    /// assert_eq!( topset.into_sorted_vec(), vec![(3,'c'), (3,'b')]);
+    /// ```
+    pub fn new_with_tiebreak<C1,C2>(n: usize, primary: C1, secondary: C2) -> TopSet<X, impl Fn(&X,&X) -> bool>
+        where C1: Fn(&X,&X) -> bool, C2: Fn(&X,&X) -> bool
+    {
+        TopSet::new(n, lexicographic(primary, secondary))
+    }
+
+    /// Creates a new top set with a mutable challenge.
+    ///
+    /// Unlike [`TopSet::new`], the closure `beat` may mutate its captured
+    /// state (e.g. to cache expensive keys). It is moved into the top set
+    /// and called through a [`RefCell`] (it is never called reentrantly),
+    /// so the returned top set is not [`Sync`].
+    ///
+    /// See [`crate::build_scoped`] to borrow the mutable state only for a one-shot selection.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut duels = 0;
+    /// let mut topset = TopSet::new_mut(2, move |a: &u32, b: &u32| { duels += 1; a > b });
+    /// topset.extend(vec![7,5,6,9,4,2,3]);
+    /// assert_eq!( topset.into_sorted_vec(), vec![7,9]);
+    /// ```
+    pub fn new_mut<F>(n: usize, beat: F) -> TopSet<X, impl Fn(&X,&X) -> bool>
+        where F: FnMut(&X,&X) -> bool
+    {
+        let beat = RefCell::new(beat);
+        TopSet::new(n, move |a: &X, b: &X| (beat.borrow_mut())(a,b))
+    }
+
+    /// Creates a builder to configure a top set.
+    ///
+    /// See [`TopSetBuilder`].
+    pub fn builder() -> TopSetBuilder<X,(),Empty<X>,NoHook>
+    {
+        TopSetBuilder::new()
+    }
+}
+
+impl<X: Ord> TopSet<X, fn(&X,&X) -> bool>
+{
+    /// Creates a new top set of the `n` greatest items according to [`Ord`].
+    ///
+    /// The challenge is a zero-sized function item (not a function pointer)
+    /// so there is no indirect call and no storage for it.
+    /// It is `Copy`, so the top set could be cloned.
+    ///
+    /// The type of this challenge cannot be named. There is no nameable `Top`
+    /// comparator type since a struct cannot implement [`Fn`] on stable Rust.
+    /// When the type should be written (e.g. for a struct field), a function
+    /// pointer is used instead, at the cost of an indirect call.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::top(2);
+    /// topset.extend(vec![7,5,6,9,4,2,3]);
+    /// assert_eq!( topset.clone().into_sorted_vec(), vec![7,9]);
+    ///
+    /// // the same selection with a nameable type
+    /// let named: TopSet<u32, fn(&u32,&u32) -> bool> = TopSet::new(2, u32::gt);
+    /// assert_eq!( named.capacity(), topset.capacity());
+    /// ```
+    pub fn top(n: usize) -> TopSet<X, impl Fn(&X,&X) -> bool + Copy>
+    {
+        TopSet::new(n, ord_gt::<X>)
+    }
+
+    /// Creates a new top set of the `n` lowest items according to [`Ord`].
+    ///
+    /// See [`Self::top`] (and why its challenge type cannot be named).
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::bottom(2);
+    /// topset.extend(vec![7,5,6,9,4,2,3]);
+    /// assert_eq!( topset.into_sorted_vec(), vec![3,2]);
+    /// ```
+    pub fn bottom(n: usize) -> TopSet<X, impl Fn(&X,&X) -> bool + Copy>
+    {
+        TopSet::new(n, ord_lt::<X>)
+    }
+}
+
+impl TopSet<f64, fn(&f64,&f64) -> bool>
+{
+    /// Creates a new top set of the greatest floats, including `NaN`.
+    ///
+    /// Using [`f64::gt`] as challenge breaks the total ordering as soon as
+    /// a `NaN` is inserted. This top set relies on [`f64::total_cmp`] instead,
+    /// so a positive `NaN` is greater than any other float.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::new_total_f64(2);
+    /// topset.extend(vec![1.5, f64::NAN, -3., 8.]);
+    /// assert_eq!( topset.pop(), Some(8.) );
+    /// assert!( topset.pop().unwrap().is_nan() );
+    /// ```
+    pub fn new_total_f64(n: usize) -> Self
+    {
+        Self::new(n, greatest_f64())
+    }
+}
+
+impl TopSet<f32, fn(&f32,&f32) -> bool>
+{
+    /// Creates a new top set of the greatest floats, including `NaN`.
+    ///
+    /// See [`TopSet::new_total_f64`].
+    pub fn new_total_f32(n: usize) -> Self
+    {
+        Self::new(n, greatest_f32())
+    }
+}
+
+impl<X: MaybeNan> TopSet<X, fn(&X,&X) -> bool>
+{
+    /// Creates a new top set of floats whose challenge is checked against `NaN`.
+    ///
+    /// A challenge such as [`f64::gt`] is not a total order as soon as a `NaN`
+    /// is involved, which silently corrupts the top set. Here, in debug builds,
+    /// each duel panics if one of the items is a `NaN` (in release builds,
+    /// `beat` is used as is). To actually select `NaN`s, use [`TopSet::new_total_f64`].
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let mut topset = TopSet::new_float(2, f64::gt);
+    /// topset.extend(vec![1.5, -3., 8.]);
+    /// assert_eq!( topset.into_sorted_vec(), vec![1.5, 8.]);
+    /// ```
+    pub fn new_float<C>(n: usize, beat: C) -> TopSet<X, impl Fn(&X,&X) -> bool>
+        where C: Fn(&X,&X) -> bool
+    {
+        TopSet::new(n, move |a: &X, b: &X| {
+            debug_assert!(!a.is_nan_value() && !b.is_nan_value(),
+                "a NaN is not comparable with this challenge (e.g. f64::gt), consider TopSet::new_total_f64");
+            beat(a, b)
+        })
+    }
+}
+
+// internal stuff
+// the challenge deduced from a comparison function
+pub(crate) fn ordering_beat<X>(cmp: impl Fn(&X,&X) -> Ordering) -> impl Fn(&X,&X) -> bool
+{
+    move |a, b| cmp(a,b) == Ordering::Greater
+}
+
+// internal stuff
+// the challenge which uses `secondary` only to break the ties of `primary`
+fn lexicographic<X>(primary: impl Fn(&X,&X) -> bool, secondary: impl Fn(&X,&X) -> bool) -> impl Fn(&X,&X) -> bool
+{
+    move |a, b| primary(a,b) || (!primary(b,a) && secondary(a,b))
+}
+
+// internal stuff
+// the challenges deduced from a total order
+fn ord_gt<X: Ord>(a: &X, b: &X) -> bool { a.cmp(b) == Ordering::Greater }
+fn ord_lt<X: Ord>(a: &X, b: &X) -> bool { a.cmp(b) == Ordering::Less }
+
+impl<X,C> IntoIterator for TopSet<X,C>
+    where C: Fn(&X,&X) -> bool
+{
+    type Item = X;
+    type IntoIter = <Vec<X> as IntoIterator>::IntoIter;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.heap.into_iter()
+    }
+}
+
+impl<'a,X,C> IntoIterator for &'a TopSet<X,C>
+    where C: Fn(&X,&X) -> bool
+{
+    type Item = &'a X;
+    type IntoIter = <&'a Vec<X> as IntoIterator>::IntoIter;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.heap.iter()
+    }
+}
+
+impl<X,C> Extend<X> for TopSet<X,C>
+    where C: Fn(&X,&X) -> bool
+{
+    #[inline]
+    fn extend<T: IntoIterator<Item=X>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|x| { self.insert(x); } )
+    }
+}
+
+/// Two top sets are equal if they store the same items (with the same multiplicities).
+///
+/// The internal layout of the heaps does not matter and the challenging
+/// functions are **not** compared (so top sets with different kinds of
+/// challenges could be compared).
+///
+/// # Example
+/// ```
+/// # use topset::TopSet;
+/// let a = TopSet::with_init(3, u32::gt, vec![7,5,6,9,4,2,3] );
+/// let b = TopSet::with_init(5, |x: &u32, y: &u32| x > y, vec![9,7,6] );
+/// assert_eq!( a, b );
+/// assert_ne!( a, TopSet::with_init(3, u32::gt, vec![9,7,7]) );
+/// ```
+impl<X,C,C2> PartialEq<TopSet<X,C2>> for TopSet<X,C>
+    where X: Ord, C: Fn(&X,&X) -> bool, C2: Fn(&X,&X) -> bool
+{
+    fn eq(&self, other: &TopSet<X,C2>) -> bool {
+        self.heap.len() == other.heap.len() && self.canonical() == other.canonical()
+    }
+}
+
+impl<X,C> Eq for TopSet<X,C>
+    where X: Ord, C: Fn(&X,&X) -> bool
+{ }
+
+/// The hash only depends on the stored items, as the equality.
+///
+/// # Example
+/// ```
+/// # use topset::TopSet;
+/// # use std::collections::HashMap;
+/// let mut cache = HashMap::new();
+/// cache.insert(TopSet::with_init(3, u32::gt, vec![7,5,6,9,4,2,3] ), "first");
+/// assert_eq!( cache.get(&TopSet::with_init(3, u32::gt, vec![9,6,7]) ), Some(&"first") );
+/// ```
+impl<X,C> Hash for TopSet<X,C>
+    where X: Hash + Ord, C: Fn(&X,&X) -> bool
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.canonical().hash(state)
+    }
+}
+
+impl<X,C> Debug for TopSet<X,C>
+    where X:Debug, C: Fn(&X,&X) -> bool
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.heap.fmt(f)
+    }
+}
+
+/// Displays the stored items sorted from the _greatest_ to the _lowest_.
+///
+/// Unlike [`Debug`] (which shows the internal heap order), the items are sorted
+/// (see [`TopSet::iter_sorted`]).
+///
+/// # Example
+/// ```
+/// # use topset::TopSet;
+/// let topset = TopSet::with_init(3, u32::gt, vec![7,5,6,9,4,2,3] );
+/// assert_eq!( topset.to_string(), "[9, 7, 6]" );
+/// assert_eq!( TopSet::<u32,_>::new(3, u32::gt).to_string(), "[]" );
+/// ```
+impl<X,C> Display for TopSet<X,C>
+    where X: Display, C: Fn(&X,&X) -> bool
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let sorted = self.iter_sorted().collect::<Vec<_>>();
+        write!(f, "[")?;
+        for (i, x) in sorted.into_iter().rev().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            x.fmt(f)?;
+        }
+        write!(f, "]")
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use alloc::boxed::Box;
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+    use std::collections::BinaryHeap;
+    use crate::iter::TopSetReducing;
+    use crate::{BoxedTopSet, TopSet};
+
+    #[test]
+    fn lowest_cost()
+    {
+        let mut top = TopSet::<f32,_>::new(5, f32::lt);
+        top.extend(vec![81.5, 4.5, 4., 1., 45., 22., 11.]);
+        top.extend(vec![81.5, 4.5, 4., 1., 45., 22., 11.]);
+
+        assert_eq![ top.pop(), Some(4.5) ];
+        assert_eq![ top.pop(), Some(4.) ];
+        assert_eq![ top.pop(), Some(4.) ];
+        assert_eq![ top.pop(), Some(1.) ];
+        assert_eq![ top.pop(), Some(1.) ];
+        assert_eq![ top.pop(), None ];
+    }
+
+    #[test]
+    fn greatest_score()
+    {
+        assert_eq![
+            vec![81,5, 4,5,4,1,45,22,1,5,97,5,877,12,0]
+                .topset(5, u32::gt)
+                .into_iter()
+                .last(),
             Some(877)];
     }
+
+    #[test]
+    fn rebuild_from_vec()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        let top = TopSet::with_init(6, u32::gt, items.clone());
+        let sorted = top.clone().into_sorted_vec();
+
+        let mut shuffled = top.into_vec();
+        shuffled.reverse();
+        let top = TopSet::rebuild(6, u32::gt, shuffled);
+        assert_eq![ top.peek(), Some(&12) ];
+        assert_eq![ top.into_sorted_vec(), sorted ];
+
+        assert_eq![ TopSet::rebuild(4, u32::gt, items.clone()).into_sorted_vec(), vec![45, 81, 97, 877] ];
+        assert_eq![ TopSet::rebuild(20, u32::gt, items.clone()).len(), items.len() ];
+        assert!( TopSet::rebuild(0, u32::gt, items).is_empty() );
+    }
+
+    #[test]
+    fn eviction_ages()
+    {
+        let mut top = TopSet::new(3, u32::gt);
+        assert_eq![ top.insert_tracked_age(10), None ];
+        assert_eq![ top.insert_tracked_age(20), None ];
+        assert_eq![ top.insert_tracked_age(30), None ];
+        assert_eq![ top.insert_tracked_age(5), Some((5, 0)) ];
+        assert_eq![ top.insert_tracked_age(40), Some((10, 3)) ];
+        assert_eq![ top.insert_tracked_age(50), Some((20, 3)) ];
+        assert_eq![ top.insert_tracked_age(35), Some((30, 3)) ];
+        assert_eq![ top.insert_tracked_age(60), Some((35, 0)) ];
+        assert_eq![ top.insert_tracked_age(45), Some((40, 3)) ];
+        assert_eq![ top.pop(), Some(45) ];
+        assert_eq![ top.insert_tracked_age(1), None ];
+        assert_eq![ top.insert_tracked_age(2), Some((1, 0)) ];
+
+        // the ages are tracked from the first call
+        let mut top = TopSet::with_init(2, u32::gt, vec![10, 20, 30]);
+        assert_eq![ top.stamps.len(), 0 ];
+        assert_eq![ top.insert_tracked_age(25), Some((20, 0)) ];
+        assert_eq![ top.insert_tracked_age(5), Some((5, 0)) ];
+        assert_eq![ top.insert_tracked_age(40), Some((25, 1)) ];
+        assert_eq![ top.insert_tracked_age(50), Some((30, 3)) ];
+        assert_eq![ top.stamps.len(), 2 ];
+    }
+
+    #[test]
+    fn refined_ties()
+    {
+        let items = vec![(5, 'a'), (3, 'b'), (5, 'c'), (3, 'd'), (1, 'e'), (5, 'f')];
+        let top = TopSet::with_init(5, |a: &(u32, char), b| a.0 > b.0, items);
+
+        let sorted = top.clone().refine(|a, b| a.1 > b.1).into_iter_sorted().collect::<Vec<_>>();
+        assert_eq![ sorted, vec![(3, 'b'), (3, 'd'), (5, 'a'), (5, 'c'), (5, 'f')] ];
+
+        let sorted = top.refine(|a, b| a.1 < b.1).into_iter_sorted().collect::<Vec<_>>();
+        assert_eq![ sorted, vec![(3, 'd'), (3, 'b'), (5, 'f'), (5, 'c'), (5, 'a')] ];
+    }
+
+    #[test]
+    fn equal_items_are_cheap()
+    {
+        let duels = std::cell::Cell::new(0usize);
+        let mut top = TopSet::new(10, |a: &u32, b: &u32| { duels.set(duels.get() + 1); a > b });
+        for _ in 0..1_000_000 {
+            top.insert(42);
+        }
+        assert_eq![ top.len(), 10 ];
+        assert!( top.iter().all(|&x| x == 42) );
+        // one duel per rejected item, plus the filling of the heap
+        assert!( duels.get() <= 1_000_000 + 10 );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn histogram_by_endpoint()
+    {
+        let requests = vec![
+            ("/login", 120), ("/search", 450), ("/login", 80), ("/search", 300),
+            ("/upload", 900), ("/login", 200), ("/search", 20), ("/upload", 10)
+        ];
+        let top = TopSet::with_init(5, |a: &(&str, u32), b| a.1 > b.1, requests);
+        let counts = top.histogram(|r| r.0);
+        assert_eq![ counts.len(), 3 ];
+        assert_eq![ counts["/search"], 2 ];
+        assert_eq![ counts["/login"], 2 ];
+        assert_eq![ counts["/upload"], 1 ];
+        assert_eq![ top.len(), 5 ];
+    }
+
+    #[test]
+    fn two_ended_consumption()
+    {
+        let top = TopSet::with_init(6, u32::gt, vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0]);
+        let mut deque = top.into_sorted_deque();
+        let mut interleaved = vec![];
+        while let Some(high) = deque.pop_back() {
+            interleaved.push(high);
+            if let Some(low) = deque.pop_front() {
+                interleaved.push(low);
+            }
+        }
+        assert_eq![ interleaved, vec![877, 12, 97, 22, 81, 45] ];
+    }
+
+    #[test]
+    fn expanded_batch()
+    {
+        let mut top = TopSet::new(4, |a: &(char, u32), b| a.1 > b.1);
+        top.extend(vec![('x', 10), ('y', 20)]);
+        let batch = ('b', vec![5, 30, 15]);
+        let removed = top.insert_expanded(('b', 0), |(c, _)| batch.1.iter().map(move |&s| (c, s)).collect::<Vec<_>>());
+        assert_eq![ removed, vec![('b', 5)] ];
+        assert_eq![ top.len(), 4 ];
+        assert_eq![ top.into_sorted_vec(), vec![('x', 10), ('b', 15), ('y', 20), ('b', 30)] ];
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn merge_shards_by_user()
+    {
+        let beat = |a: &(&str, u32), b: &(&str, u32)| a.1 > b.1;
+        let mut shard1 = TopSet::with_init(4, beat, vec![("ann", 50), ("bob", 70), ("cid", 10), ("dan", 40)]);
+        let shard2 = TopSet::with_init(4, beat, vec![("ann", 90), ("bob", 20), ("eve", 60), ("cid", 30)]);
+        shard1.merge_dedup_by_key(shard2, |x| x.0);
+        assert_eq![ shard1.len(), 4 ];
+        assert_eq![ shard1.into_sorted_vec(), vec![("dan", 40), ("eve", 60), ("bob", 70), ("ann", 90)] ];
+
+        let mut small = TopSet::with_init(2, beat, vec![("ann", 1)]);
+        small.merge_dedup_by_key(TopSet::with_init(2, beat, vec![("ann", 3), ("bob", 2)]), |x| x.0);
+        assert_eq![ small.into_sorted_vec(), vec![("bob", 2), ("ann", 3)] ];
+    }
+
+    #[test]
+    fn memory_pressure()
+    {
+        let mut top = TopSet::with_init(5, u32::gt, 0..10);
+        top.set_effective_capacity(2);
+        assert_eq![ top.len(), 2 ];
+        assert_eq![ top.capacity(), 5 ];
+        assert!( !top.is_candidate(&7) );
+        assert_eq![ top.insert(20), Some(8) ];
+
+        top.resize(10);
+        assert_eq![ top.effective_capacity(), 2 ];
+        assert_eq![ top.insert(1), Some(1) ];
+
+        top.set_effective_capacity(usize::MAX);
+        assert_eq![ top.effective_capacity(), 10 ];
+        top.extend(0..5);
+        assert_eq![ top.len(), 7 ];
+
+        top.set_effective_capacity(0);
+        assert!( top.is_empty() );
+        assert_eq![ top.insert(100), Some(100) ];
+    }
+
+    #[test]
+    fn spread_of_retained()
+    {
+        let items = vec![81.5, 4.5, 4., 1., 45., 22., 11.];
+        let top = TopSet::with_init(4, f64::gt, items.clone());
+        assert_eq![ top.spread(), Some(81.5 - 11.) ];
+        let top = TopSet::with_init(4, f64::lt, items);
+        assert_eq![ top.spread(), Some(1. - 11.) ];
+
+        assert_eq![ TopSet::with_init(4, i32::gt, vec![7]).spread(), Some(0) ];
+        assert_eq![ TopSet::new(4, i32::gt).spread(), None ];
+    }
+
+    #[test]
+    fn boxed_strategies()
+    {
+        let token = std::sync::Mutex::new(()); // neither Clone nor Copy
+        let mut strategies: Vec<BoxedTopSet<u32>> = vec![
+            TopSet::boxed(3, u32::gt),
+            TopSet::boxed(3, move |a, b| { let _guard = token.lock(); a % 10 > b % 10 }),
+        ];
+        strategies.iter_mut().for_each(|top| top.extend(vec![19, 42, 7, 100, 55, 38]));
+        let ranked = strategies.into_iter().map(|top| top.into_sorted_vec()).collect::<Vec<_>>();
+        assert_eq![ ranked, vec![vec![42, 55, 100], vec![7, 38, 19]] ];
+    }
+
+    #[test]
+    fn presentation_order()
+    {
+        let items = vec![(90, 3), (75, 1), (90, 1), (60, 2), (80, 2)];
+        let top = TopSet::with_init(3, |a: &(u32, u32), b| a.0 > b.0, items);
+        let by_id = top.into_sorted_vec_with(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)));
+        assert_eq![ by_id, vec![(90, 1), (80, 2), (90, 3)] ];
+    }
+
+    #[test]
+    fn eviction_preview()
+    {
+        let mut top = TopSet::with_init(3, u32::gt, vec![10, 30]);
+        assert_eq![ top.would_evict(&50), None ];
+        top.insert(20);
+        assert_eq![ top.would_evict(&50), Some(&10) ];
+        assert_eq![ top.would_evict(&10), None ];
+        assert_eq![ top.would_evict(&5), None ];
+        assert_eq![ top.insert(50), Some(10) ];
+        assert_eq![ TopSet::new(0, u32::gt).would_evict(&1), None ];
+    }
+
+    #[test]
+    fn refill_in_place()
+    {
+        let mut top = TopSet::with_init(4, u32::gt, vec![81, 5, 4, 5, 4, 1, 45, 22]);
+        let storage = top.heap.as_ptr();
+        top.refill(vec![3, 9, 12, 7, 1, 8, 2]);
+        assert_eq![ top.heap.as_ptr(), storage ];
+        assert_eq![ top.capacity(), 4 ];
+        assert_eq![ top.clone().into_sorted_vec(), vec![7, 8, 9, 12] ];
+
+        top.refill(vec![6]);
+        assert_eq![ top.heap.as_ptr(), storage ];
+        assert_eq![ top.into_sorted_vec(), vec![6] ];
+    }
+
+    #[test]
+    fn reversed_empty_set()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        let mut lowest = TopSet::new(4, u32::gt).reversed();
+        assert_eq![ lowest.capacity(), 4 ];
+        lowest.extend(items.clone());
+        assert_eq![ lowest.pop(), Some(4) ];
+        assert_eq![ lowest.into_sorted_vec(), vec![1, 1, 0] ];
+
+        // the stored items are kept, but re-arranged
+        let flipped = TopSet::with_init(3, u32::gt, items).reversed();
+        assert_eq![ flipped.peek(), Some(&877) ];
+        assert_eq![ flipped.into_sorted_vec(), vec![877, 97, 81] ];
+    }
+
+    #[test]
+    fn fractional_ranks()
+    {
+        let top = TopSet::with_init(11, u32::gt, (0..100).rev());
+        assert_eq![ top.value_at_fraction(0.), Some(&89) ];
+        assert_eq![ top.value_at_fraction(0.1), Some(&90) ];
+        assert_eq![ top.value_at_fraction(0.5), Some(&94) ];
+        assert_eq![ top.value_at_fraction(0.9), Some(&98) ];
+        assert_eq![ top.value_at_fraction(1.), Some(&99) ];
+        assert_eq![ top.value_at_fraction(-3.), Some(&89) ];
+        assert_eq![ top.value_at_fraction(42.), Some(&99) ];
+
+        assert_eq![ TopSet::with_init(3, u32::gt, vec![5]).value_at_fraction(0.3), Some(&5) ];
+        assert_eq![ TopSet::new(3, u32::gt).value_at_fraction(0.5), None ];
+    }
+
+    #[test]
+    fn change_detection()
+    {
+        let mut top = TopSet::with_init(3, u32::gt, vec![10, 20, 30]);
+        assert_eq![ top.insert_changed(5), (Some(5), false) ];
+        assert_eq![ top.insert_changed(10), (Some(10), false) ];
+        assert_eq![ top.insert_changed(15), (Some(10), true) ];
+        assert_eq![ top.clone().into_sorted_vec(), vec![15, 20, 30] ];
+        top.pop();
+        assert_eq![ top.insert_changed(1), (None, true) ];
+    }
+
+    #[test]
+    fn trending_decay()
+    {
+        let mut top = TopSet::new(2, |a: &(char, f64), b| a.1 > b.1);
+        top.extend(vec![('s', 100.), ('t', 90.)]);
+        for _ in 0..3 {
+            top.apply_decay(|x| x.1 *= 0.5);
+        }
+        assert_eq![ top.peek(), Some(&('t', 11.25)) ];
+        assert_eq![ top.insert(('u', 50.)), Some(('t', 11.25)) ];
+        assert_eq![ top.insert(('v', 40.)), Some(('s', 12.5)) ];
+
+        // a non uniform decay changes the ranking
+        top.apply_decay(|x| if x.0 == 'u' { x.1 = 0. });
+        assert_eq![ top.peek(), Some(&('u', 0.)) ];
+    }
+
+    #[test]
+    fn novel_entries()
+    {
+        let baseline = ["ann", "bob", "cid"];
+        let beat = |a: &(&str, u32), b: &(&str, u32)| a.1 > b.1;
+        let top = TopSet::with_init(4, beat, vec![("ann", 5), ("eve", 9), ("bob", 7), ("dan", 1), ("fay", 6)]);
+        let mut novel = top.novel_against(|x| baseline.contains(&x.0));
+        novel.sort();
+        assert_eq![ novel, vec![&("eve", 9), &("fay", 6)] ];
+        assert_eq![ top.novel_against(|_| true), Vec::<&(&str, u32)>::new() ];
+        assert_eq![ top.novel_against(|_| false).len(), 4 ];
+    }
+
+    #[test]
+    fn reconfigured_query()
+    {
+        let items = vec![(5, 'e'), (3, 'b'), (9, 'z'), (1, 'a'), (7, 'c'), (2, 'y')];
+        let top = TopSet::with_init(5, |a: &(u32, char), b| a.0 > b.0, items);
+        assert_eq![ top.clone().into_sorted_vec(), vec![(2, 'y'), (3, 'b'), (5, 'e'), (7, 'c'), (9, 'z')] ];
+
+        let shrunk = top.clone().reconfigure(2, |a: &(u32, char), b| a.1 < b.1);
+        assert_eq![ shrunk.capacity(), 2 ];
+        assert_eq![ shrunk.into_sorted_vec(), vec![(7, 'c'), (3, 'b')] ];
+
+        let mut grown = top.reconfigure(8, |a: &(u32, char), b| a.1 > b.1);
+        assert_eq![ grown.len(), 5 ];
+        assert_eq![ grown.insert((0, 'a')), None ];
+        assert_eq![ grown.peek(), Some(&(0, 'a')) ];
+    }
+
+    #[test]
+    fn qualifying_rank()
+    {
+        let top = TopSet::with_init(4, u32::lt, vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97]);
+        // the topset contains { 1, 1, 4, 4 }
+        assert_eq![ top.min_n_to_include(&0), 1 ];
+        assert_eq![ top.min_n_to_include(&1), 3 ];
+        assert_eq![ top.min_n_to_include(&2), 3 ];
+        assert_eq![ top.min_n_to_include(&4), 5 ];
+        assert_eq![ top.min_n_to_include(&50), 5 ];
+
+        let empty = TopSet::<u32,_>::new(4, u32::lt);
+        assert_eq![ empty.min_n_to_include(&50), 1 ];
+    }
+
+    #[test]
+    fn deterministic_ties()
+    {
+        let items = [(2, 'a'), (5, 'b'), (2, 'c'), (5, 'd'), (1, 'e'), (5, 'f'), (2, 'g'), (5, 'h')];
+        let build = || {
+            let mut top = TopSet::new_deterministic(5, |a: &(u32, char), b| a.0 > b.0);
+            top.extend(items.iter().copied());
+            top
+        };
+        let first = build().iter().copied().collect::<Vec<_>>();
+        for _ in 0..5 {
+            assert_eq![ build().iter().copied().collect::<Vec<_>>(), first ];
+        }
+
+        // the earliest ties are kept and the latest ones are the lowest
+        let top = build();
+        assert_eq![ top.into_sorted_vec(), vec![(2, 'a'), (5, 'h'), (5, 'f'), (5, 'd'), (5, 'b')] ];
+        let mut top = build();
+        assert_eq![ top.insert((2, 'z')), Some((2, 'z')) ];
+        assert_eq![ top.insert((5, 'z')), Some((2, 'a')) ];
+        assert_eq![ top.drain().collect::<Vec<_>>().len(), 5 ];
+    }
+
+    #[test]
+    fn max_heap_handoff()
+    {
+        let top = TopSet::with_init(5, u32::lt, vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97]);
+        let vec = top.into_max_heap_vec();
+        assert_eq![ vec.len(), 5 ];
+        assert!( (1..vec.len()).all(|i| vec[(i-1)/2] >= vec[i]) );
+
+        let mut heap = BinaryHeap::from(vec);
+        assert_eq![ heap.pop(), Some(5) ];
+        heap.push(3);
+        assert_eq![ heap.into_sorted_vec(), vec![1, 1, 3, 4, 4] ];
+    }
+
+    #[test]
+    fn repaired_after_edits()
+    {
+        let mut top = TopSet::with_init(4, |a: &(u32, u32), b| a.1 > b.1, (0..10).map(|i| (i, i)));
+        top.as_mut_slice().iter_mut().for_each(|x| x.1 = (x.0 * 7) % 10);
+        top.repair();
+        assert_eq![ top.pop(), Some((6, 2)) ];
+        assert_eq![ top.insert((1, 5)), None ];
+        assert_eq![ top.into_sorted_vec(), vec![(9, 3), (1, 5), (8, 6), (7, 9)] ];
+
+        // an edit which keeps the heap valid is not detected as an error
+        let mut top = TopSet::with_init(4, u32::gt, 0..10);
+        top.as_mut_slice().iter_mut().for_each(|x| *x += 1);
+        assert_eq![ top.peek(), Some(&7) ];
+
+        // the heap is also restored by clearing or rebuilding it
+        fn edit<C: Fn(&u32,&u32) -> bool>(top: &mut TopSet<u32,C>) { top.as_mut_slice()[0] = 100; }
+        let mut top = TopSet::with_init(4, u32::gt, 0..10);
+        edit(&mut top);
+        top.clear();
+        assert!( !top.dirty );
+        top.extend(0..10);
+        edit(&mut top);
+        let _ = top.drain();
+        assert!( !top.dirty );
+        top.extend(0..10);
+        edit(&mut top);
+        top.retain(|x| x % 2 == 0);
+        assert!( !top.dirty );
+        assert_eq![ top.pop(), Some(8) ];
+        edit(&mut top);
+        top.refill(0..10);
+        assert!( !top.dirty );
+        edit(&mut top);
+        top.apply_decay(|x| *x /= 2);
+        assert!( !top.dirty );
+        edit(&mut top);
+        top.absorb(TopSet::with_init(4, u32::gt, 0..10));
+        assert!( !top.dirty );
+        assert_eq![ top.into_sorted_vec(), vec![8, 9, 50, 100] ];
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "without calling repair")]
+    fn unrepaired_edits()
+    {
+        let mut top = TopSet::with_init(4, u32::gt, 0..10);
+        top.as_mut_slice()[0] = 100;
+        top.pop();
+    }
+
+    #[test]
+    fn current_leader()
+    {
+        let mut top = TopSet::new(4, u32::gt);
+        assert_eq![ top.peek_best(), None ];
+        top.insert(5);
+        assert_eq![ top.peek(), Some(&5) ];
+        assert_eq![ top.peek_best(), Some(&5) ];
+        for x in [81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0] {
+            top.insert(x);
+            assert_eq![ top.peek_best(), top.clone().into_sorted_vec().last() ];
+        }
+        assert_eq![ top.peek_best(), Some(&877) ];
+    }
+
+    #[test]
+    fn sorted_without_equality()
+    {
+        // the handles can not be compared, only their priority is meaningful
+        struct Task { priority: u32, run: Box<dyn Fn() -> u32> }
+
+        let tasks = [3, 8, 1, 8, 5, 2].map(|p| Task { priority: p, run: Box::new(move || 10*p) });
+        let top = TopSet::with_init(4, |a: &Task, b: &Task| a.priority > b.priority, tasks);
+        let sorted = top.into_sorted_vec();
+        assert_eq![ sorted.iter().map(|t| t.priority).collect::<Vec<_>>(), vec![3, 5, 8, 8] ];
+        assert_eq![ (sorted[1].run)(), 50 ];
+    }
+
+    #[test]
+    fn repeated_sorted_iteration()
+    {
+        let mut top = TopSet::with_init(5, u32::lt, vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97]);
+        let layout = top.iter().copied().collect::<Vec<_>>();
+        for _ in 0..3 {
+            assert_eq![ top.iter_sorted().copied().collect::<Vec<_>>(), vec![5, 4, 4, 1, 1] ];
+        }
+        assert_eq![ top.iter().copied().collect::<Vec<_>>(), layout ];
+
+        top.insert(2);
+        assert_eq![ top.iter_sorted().copied().collect::<Vec<_>>(), vec![4, 4, 2, 1, 1] ];
+        assert_eq![ TopSet::<u32,_>::new(2, u32::lt).iter_sorted().next(), None ];
+    }
+
+    #[test]
+    fn sorted_draining()
+    {
+        let mut top = TopSet::with_init(5, u32::lt, vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97]);
+        let mut drain = top.drain_sorted();
+        assert_eq![ drain.size_hint(), (5, Some(5)) ];
+        assert_eq![ drain.next(), Some(5) ];
+        assert_eq![ drain.next(), Some(4) ];
+        assert_eq![ drain.len(), 3 ];
+        drop(drain);
+        assert!( top.is_empty() );
+
+        // the top set is still usable
+        top.extend(vec![3, 2, 8]);
+        assert_eq![ top.drain_sorted().collect::<Vec<_>>(), vec![8, 3, 2] ];
+        assert_eq![ top.drain_sorted().next(), None ];
+    }
+
+    #[test]
+    fn retained_connections()
+    {
+        // (connection id, score)
+        let mut top = TopSet::with_init(5, |a: &(u32, u32), b| a.1 > b.1, (0..10).map(|i| (i, (i * 7) % 10)));
+        assert_eq![ top.peek(), Some(&(5, 5)) ];
+
+        let mut visited = 0;
+        top.retain(|c| { visited += 1; c.0 != 5 && c.0 != 1 });
+        assert_eq![ visited, 5 ];
+        assert_eq![ top.len(), 3 ];
+        assert_eq![ top.peek(), Some(&(8, 6)) ];
+        assert!( top.is_valid_heap() );
+        assert_eq![ top.insert((10, 0)), None ];
+        assert_eq![ top.pop(), Some((10, 0)) ];
+        assert_eq![ top.into_sorted_vec(), vec![(8, 6), (4, 8), (7, 9)] ];
+    }
+
+    #[test]
+    fn absorbed_shards()
+    {
+        let items = (0..1000u32).map(|i| (i * 7919) % 1009).collect::<Vec<_>>();
+        let mut shards = items.chunks(300)
+            .map(|chunk| TopSet::with_init(10, u32::gt, chunk.iter().copied()))
+            .collect::<Vec<_>>();
+        let mut top = shards.remove(0);
+        shards.into_iter().for_each(|shard| top.absorb(shard));
+        assert_eq![ top.len(), 10 ];
+        assert_eq![ top.clone().into_sorted_vec(), TopSet::with_init(10, u32::gt, items).into_sorted_vec() ];
+
+        // the capacity of the absorbing top set prevails
+        let mut small = TopSet::with_init(2, u32::gt, vec![1, 2]);
+        small.absorb(top);
+        assert_eq![ small.capacity(), 2 ];
+        assert_eq![ small.into_sorted_vec(), vec![1007, 1008] ];
+        let mut empty = TopSet::new(4, u32::gt);
+        empty.absorb(TopSet::with_init(2, u32::gt, vec![5, 3, 8]));
+        assert_eq![ empty.insert(1), None ];
+        assert_eq![ empty.into_sorted_vec(), vec![1, 5, 8] ];
+    }
+
+    #[test]
+    fn board_position()
+    {
+        let top = TopSet::with_init(4, u32::lt, vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97]);
+        // the topset contains { 1, 1, 4, 4 }
+        assert!( top.contains(&4) );
+        assert!( ! top.contains(&5) );
+        assert_eq![ top.rank_of(&0), 0 ];
+        assert_eq![ top.rank_of(&1), 0 ];
+        assert_eq![ top.rank_of(&3), 2 ];
+        assert_eq![ top.rank_of(&4), 2 ];
+        assert_eq![ top.rank_of(&5), 4 ];
+
+        let empty = TopSet::<u32,_>::new(4, u32::lt);
+        assert!( ! empty.contains(&5) );
+        assert_eq![ empty.rank_of(&5), 0 ];
+    }
+
+    #[test]
+    fn ord_challenges()
+    {
+        fn beat_size<X, C: Fn(&X,&X) -> bool>(_: &TopSet<X,C>) -> usize { std::mem::size_of::<C>() }
+
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        let mut top = TopSet::top(5);
+        top.extend(items.iter().copied());
+        assert_eq![ beat_size(&top), 0 ];
+        assert_eq![ top.clone().into_sorted_vec(), vec![22, 45, 81, 97, 877] ];
+
+        let bottom = TopSet::bottom(3);
+        let bottom = items.into_iter().fold(bottom, |mut b, x| { b.insert(x); b });
+        assert_eq![ beat_size(&bottom), 0 ];
+        assert_eq![ bottom.into_sorted_vec(), vec![1, 1, 0] ];
+    }
+
+    #[test]
+    fn zero_capacity()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97];
+
+        let mut top = TopSet::with_init(0, u32::gt, items.clone());
+        assert!( top.is_empty() );
+        assert_eq![ top.capacity(), 0 ];
+        assert_eq![ top.peek(), None ];
+        assert!( ! top.is_candidate(&1000) );
+        assert_eq![ top.would_evict(&1000), None ];
+        assert_eq![ top.insert(1000), Some(1000) ];
+        assert_eq![ top.insert_is_best(1000), (Some(1000), false) ];
+        assert_eq![ top.pop(), None ];
+        assert_eq![ top.clone().into_sorted_vec(), Vec::<u32>::new() ];
+
+        let mut top = TopSet::with_init(3, u32::gt, items.clone());
+        top.resize(0);
+        assert!( top.is_empty() );
+        assert!( ! top.is_candidate(&1000) );
+        assert_eq![ top.insert(1000), Some(1000) ];
+        assert_eq![ top.into_sorted_vec(), Vec::<u32>::new() ];
+
+        let top = TopSet::rebuild(0, u32::gt, items.clone());
+        assert!( top.is_empty() );
+        assert!( items.topset(0, u32::gt).into_iter_sorted().next().is_none() );
+    }
+
+    #[test]
+    fn fifo_ties()
+    {
+        // (score, arrival)
+        let beat = |a: &(u32, u32), b: &(u32, u32)| a.0 > b.0;
+        let mut top = TopSet::new_fifo(4, beat);
+        top.extend((0..10).map(|i| (i % 3, i)));
+        // the most recent ones among the tied items are kept
+        assert_eq![ top.clone().into_sorted_vec(), vec![(1, 7), (2, 2), (2, 5), (2, 8)] ];
+        assert_eq![ top.iter_sorted().copied().collect::<Vec<_>>(), vec![(1, 7), (2, 2), (2, 5), (2, 8)] ];
+        assert_eq![ top.insert((2, 10)), Some((1, 7)) ];
+        assert!( top.is_candidate(&(2, 11)) );
+        assert_eq![ top.would_evict(&(2, 11)), Some(&(2, 2)) ];
+        assert_eq![ top.insert((2, 11)), Some((2, 2)) ];
+        assert_eq![ top.pop(), Some((2, 5)) ];
+
+        // nothing changes for strictly ordered items
+        let items = vec![81, 5, 4, 45, 22, 1, 97, 877, 12, 0];
+        let mut top = TopSet::new_fifo(4, u32::gt);
+        top.extend(items.iter().copied());
+        assert_eq![ top.into_sorted_vec(), TopSet::with_init(4, u32::gt, items).into_sorted_vec() ];
+    }
+
+    #[test]
+    fn mutated_lowest()
+    {
+        // (hits, id)
+        let mut top = TopSet::with_init(4, |a: &(u32, u32), b| a.0 > b.0, (0..10).map(|i| (i, i)));
+        assert_eq![ top.peek(), Some(&(6, 6)) ];
+
+        // still the lowest
+        top.peek_mut().unwrap().0 += 1;
+        assert_eq![ top.peek(), Some(&(7, 6)) ];
+
+        // greater than its children
+        top.peek_mut().unwrap().0 += 10;
+        assert_eq![ top.peek(), Some(&(7, 7)) ];
+        assert_eq![ top.clone().into_sorted_vec(), vec![(7, 7), (8, 8), (9, 9), (17, 6)] ];
+        assert_eq![ top.insert((8, 10)), Some((7, 7)) ];
+
+        top.clear();
+        assert!( top.peek_mut().is_none() );
+    }
+
+    #[test]
+    fn allocated_storage()
+    {
+        let mut top = TopSet::with_init(1000, u32::gt, 0..1000);
+        assert!( top.heap.capacity() >= 1000 );
+        top.resize(3);
+        top.shrink_to_fit();
+        assert!( top.heap.capacity() < 1000 );
+        assert_eq![ top.capacity(), 3 ];
+
+        top.reserve(500);
+        assert!( top.heap.capacity() >= 503 );
+        assert_eq![ top.capacity(), 3 ];
+        top.extend(1000..1010);
+        // no stamp is needed when the ties are not broken
+        assert_eq![ top.stamps.capacity(), 0 ];
+        assert_eq![ top.into_sorted_vec(), vec![1007, 1008, 1009] ];
+
+        let mut top = TopSet::new_deterministic(3, u32::gt);
+        top.extend(0..1000);
+        assert_eq![ top.stamps.len(), 3 ];
+    }
+
+    #[test]
+    fn heapified_init()
+    {
+        // pseudo-random items with a lot of duplicates
+        let mut state = 17u64;
+        let items = (0..5000).map(|_| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 33) % 2000
+        }).collect::<Vec<_>>();
+
+        for n in [0, 1, 7, 100, 4999, 5000, 6000] {
+            let mut inserted = TopSet::new(n, u64::gt);
+            inserted.extend(items.iter().copied());
+            let heapified = TopSet::with_init(n, u64::gt, items.iter().copied());
+            assert_eq![ heapified.len(), n.min(items.len()) ];
+            assert_eq![ heapified.into_sorted_vec(), inserted.into_sorted_vec() ];
+        }
+    }
+
+    #[test]
+    fn indexed_sorted_access()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        let top = TopSet::with_init(8, i32::gt, items);
+        let sorted = top.to_sorted_vec();
+        assert_eq![ (0..top.len()).map(|i| *top.get_sorted(i).unwrap()).collect::<Vec<_>>(), sorted ];
+        assert_eq![ top.get_sorted(8), None ];
+        assert_eq![ top.get_sorted(usize::MAX), None ];
+        assert_eq![ TopSet::new(3, i32::gt).get_sorted(0), None ];
+
+        // the tied items are in the same order as in the sorted iteration
+        let mut top = TopSet::new_fifo(6, |a: &(u32,char), b: &(u32,char)| a.0 > b.0);
+        top.extend(vec![(3,'a'), (1,'b'), (3,'c'), (2,'d'), (3,'e'), (2,'f'), (3,'g'), (1,'h')]);
+        for i in 0..top.len() {
+            assert_eq![ top.get_sorted(i), top.iter_sorted().nth(i) ];
+        }
+        assert_eq![ top.value_at_fraction(1.), top.iter_sorted().last() ];
+    }
+
+    #[test]
+    fn multiset_equality()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        let a = TopSet::with_init(6, i32::gt, items.clone());
+        let mut b = TopSet::new(6, i32::gt);
+        b.extend(items.iter().rev().copied());
+        assert_eq![ a, b ];
+        assert_eq![ a, TopSet::with_init(6, |x: &i32, y: &i32| x < y, vec![877, 97, 81, 45, 22, 12]) ];
+
+        // the multiplicities matter
+        let c = TopSet::with_init(6, i32::gt, vec![5, 5, 12, 22, 45, 81]);
+        let d = TopSet::with_init(6, i32::gt, vec![5, 12, 12, 22, 45, 81]);
+        assert_ne![ c, d ];
+        assert_ne![ c, TopSet::with_init(6, i32::gt, vec![5, 12, 22, 45, 81]) ];
+        assert_eq![ TopSet::<i32,_>::new(2, i32::gt), TopSet::new(7, i32::lt) ];
+    }
+
+    #[test]
+    fn hashed_as_key()
+    {
+        use std::collections::hash_map::DefaultHasher;
+        use std::collections::HashSet;
+        use std::hash::{Hash, Hasher};
+
+        fn hash_of<T: Hash>(t: &T) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            t.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        let a = TopSet::with_init(6, i32::gt, items.clone());
+        let b = TopSet::with_init(6, i32::gt, items.iter().rev().copied());
+        assert_eq![ hash_of(&a), hash_of(&b) ];
+
+        let keys = [a, b, TopSet::with_init(6, i32::gt, vec![5, 5, 12]), TopSet::with_init(6, i32::gt, vec![5, 12, 12])]
+            .into_iter()
+            .collect::<HashSet<_>>();
+        assert_eq![ keys.len(), 3 ];
+    }
+
+    #[test]
+    fn batch_rejects()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        let mut top = TopSet::new(5, i32::gt);
+        let mut removed = top.insert_all(items.clone());
+        removed.sort();
+        assert_eq![ removed, vec![0, 1, 1, 4, 4, 5, 5, 5, 5, 12] ];
+        assert_eq![ top.len() + removed.len(), items.len() ];
+
+        let mut none = TopSet::new(0, i32::gt);
+        assert_eq![ none.insert_all(items.clone()), items ];
+        assert!( none.is_empty() );
+    }
+
+    #[test]
+    fn fullness()
+    {
+        let mut top = TopSet::new(3, i32::gt);
+        assert!( !top.is_full() );
+        assert_eq![ top.capacity_left(), 3 ];
+        top.extend(vec![5, 1]);
+        assert!( !top.is_full() );
+        assert_eq![ top.capacity_left(), 1 ];
+        top.insert(4);
+        assert!( top.is_full() );
+        assert_eq![ top.capacity_left(), 0 ];
+
+        top.resize(4);
+        assert!( !top.is_full() );
+        assert_eq![ top.capacity_left(), 1 ];
+        top.set_effective_capacity(3);
+        assert!( top.is_full() );
+        assert_eq![ top.capacity_left(), 0 ];
+        top.resize(2);
+        assert!( top.is_full() );
+        assert_eq![ top.capacity_left(), 0 ];
+        assert!( TopSet::new(0, i32::gt).is_full() );
+        assert_eq![ TopSet::new(0, i32::gt).capacity_left(), 0 ];
+    }
+
+    #[test]
+    fn appended_shards()
+    {
+        let mut top = TopSet::with_init(4, i32::gt, vec![81, 5, 4, 5, 4, 1, 45]);
+        let mut other = TopSet::with_init(6, i32::gt, vec![22, 1, 5, 97, 5, 877, 12, 0]);
+        top.append(&mut other);
+        assert!( other.is_empty() );
+        assert_eq![ other.capacity(), 6 ];
+        assert_eq![ top.to_sorted_vec(), vec![45, 81, 97, 877] ];
+
+        // the emptied set is still usable
+        other.extend(vec![3, 2]);
+        top.append(&mut other);
+        assert!( other.is_empty() );
+        assert_eq![ top.into_sorted_vec(), vec![45, 81, 97, 877] ];
+    }
+
+    #[test]
+    fn counted_churn()
+    {
+        let mut top = TopSet::new(5, i32::gt);
+        assert_eq![ top.extend_counted(vec![81, 5, 4, 5, 4, 1, 45]), 6 ];
+        assert_eq![ top.extend_counted(vec![22, 1, 5, 97, 5, 877, 12, 0]), 3 ];
+        assert_eq![ top.extend_counted(vec![12, 22]), 0 ];
+        assert_eq![ top.into_sorted_vec(), vec![22, 45, 81, 97, 877] ];
+        assert_eq![ TopSet::new(0, i32::gt).extend_counted(vec![1, 2]), 0 ];
+    }
+
+    #[test]
+    fn ordering_comparator()
+    {
+        let items = vec![(3, 'a'), (1, 'b'), (3, 'c'), (2, 'd'), (1, 'e')];
+        let mut top = TopSet::new_by(3, |a: &(i32,char), b: &(i32,char)| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+        top.extend(items.clone());
+        assert_eq![ top.into_sorted_vec(), vec![(2, 'd'), (3, 'c'), (3, 'a')] ];
+
+        let top = items.topset_by_ordering(2, |a, b| b.cmp(a));
+        assert_eq![ top.into_sorted_vec(), vec![(1, 'e'), (1, 'b')] ];
+    }
+
+    #[test]
+    fn trimmed_lowest()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        let mut top = TopSet::with_init(8, i32::gt, items);
+        assert_eq![ top.pop_n(0), Vec::<i32>::new() ];
+        assert_eq![ top.pop_n(3), vec![5, 5, 12] ];
+        assert_eq![ top.len(), 5 ];
+        assert_eq![ top.capacity(), 8 ];
+        assert_eq![ top.pop_n(usize::MAX), vec![22, 45, 81, 97, 877] ];
+        assert_eq![ top.pop_n(1), Vec::<i32>::new() ];
+    }
+
+    #[test]
+    fn random_heap_operations()
+    {
+        let mut state = 0x2545F4914F6CDD1Du64;
+        let mut random = move |bound: u64| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state % bound
+        };
+
+        for n in 0..12 {
+            let mut top = TopSet::new(n, u64::gt);
+            let mut stored = Vec::new();
+            for _ in 0..400 {
+                if random(4) == 0 {
+                    // pops are rarer than inserts, so the top set is often full
+                    let lowest = stored.iter().min().copied();
+                    assert_eq![ top.pop(), lowest ];
+                    if let Some(lowest) = lowest {
+                        stored.remove(stored.iter().position(|&x| x == lowest).unwrap());
+                    }
+                } else {
+                    let x = random(20);
+                    top.insert(x);
+                    stored.push(x);
+                    stored.sort_unstable_by(|a, b| b.cmp(a));
+                    stored.truncate(n);
+                }
+                assert_eq![ top.peek(), stored.iter().min() ];
+                assert_eq![ top.len(), stored.len() ];
+                assert!( top.is_valid_heap() );
+            }
+            let mut expected = stored.clone();
+            expected.sort_unstable();
+            assert_eq![ top.into_sorted_vec(), expected ];
+        }
+
+        // no underflow at the boundaries of the heap
+        let mut top = TopSet::<u64,_>::new(3, u64::gt);
+        super::HeapBuffer::percolate_down(&mut top, 0);
+        top.insert(4);
+        super::HeapBuffer::percolate_down(&mut top, 0);
+        assert_eq![ top.peek(), Some(&4) ];
+    }
+
+    #[test]
+    fn value_cutoff()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        let mut top = TopSet::new(5, i32::gt);
+        for x in items.iter().copied() {
+            let candidate = top.is_candidate_above(&x, &12);
+            let removed = top.insert_above(x, &12);
+            assert_eq![ candidate, removed != Some(x) ];
+        }
+        assert_eq![ top.into_sorted_vec(), vec![22, 45, 81, 97, 877] ];
+
+        // the cutoff applies even with some room left
+        let mut top = TopSet::new(10, i32::gt);
+        let rejected = items.iter().filter_map(|&x| top.insert_above(x, &12)).count();
+        assert_eq![ rejected, 9 ];
+        assert_eq![ top.into_sorted_vec(), vec![12, 22, 45, 81, 97, 877] ];
+    }
+
+    #[test]
+    fn reductions()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        let top = TopSet::with_init(4, i32::gt, items);
+        assert_eq![ top.sum::<i32>(), 1100 ];
+        assert_eq![ top.fold(i32::MAX, |acc, &x| acc.min(x)), 45 ];
+        assert_eq![ top.mean(), Some(275.) ];
+
+        let empty = TopSet::<f32,_>::new(4, f32::gt);
+        assert_eq![ empty.sum::<f32>(), 0. ];
+        assert_eq![ empty.mean(), None ];
+    }
+
+    #[test]
+    fn cloned_with_fresh_challenge()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        let mut top = BoxedTopSet::boxed(5, |a: &i32, b: &i32| a > b);
+        top.extend(items);
+        let mut copy = top.clone_with(i32::gt);
+        assert_eq![ copy, top ];
+        assert_eq![ copy.capacity(), 5 ];
+        assert_eq![ copy.insert(100), Some(22) ];
+        assert_eq![ top.into_sorted_vec(), vec![22, 45, 81, 97, 877] ];
+
+        // the heap is restored for a reversed challenge
+        let lowest = copy.clone_with(i32::lt);
+        assert_eq![ lowest.peek(), Some(&877) ];
+    }
+
+    #[test]
+    fn replaced_comparator()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        let top = TopSet::with_init(5, i32::gt, items);
+        let top = top.set_comparator(|a: &i32, b: &i32| a % 10 > b % 10);
+        assert_eq![ top.capacity(), 5 ];
+        assert_eq![ top.peek(), Some(&81) ];
+        assert_eq![ top.into_sorted_vec().iter().map(|x| x % 10).collect::<Vec<_>>(), vec![1, 2, 5, 7, 7] ];
+    }
+
+    #[test]
+    fn displayed_sorted()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        let top = TopSet::with_init(4, i32::lt, items);
+        assert_eq![ top.to_string(), "[0, 1, 1, 4]" ];
+        assert_eq![ format!("{:>3}", top), "[  0,   1,   1,   4]" ];
+        assert_eq![ TopSet::with_init(4, f64::gt, vec![2.5]).to_string(), "[2.5]" ];
+    }
+
+    #[test]
+    fn lazily_built()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        let mut top = TopSet::new(5, |a: &String, b: &String| a.parse::<i32>().unwrap() > b.parse::<i32>().unwrap());
+        let mut built = 0;
+        for x in items.iter() {
+            top.insert_with(|lowest| *x > lowest.parse::<i32>().unwrap(), || { built += 1; x.to_string() });
+        }
+        assert_eq![ built, 9 ];
+        assert_eq![ top.into_sorted_vec(), vec!["22", "45", "81", "97", "877"] ];
+
+        let mut none = TopSet::new(0, i32::gt);
+        assert_eq![ none.insert_with(|_| true, || unreachable!()), (None, false) ];
+    }
+
+    #[test]
+    fn record_breaking()
+    {
+        let mut top = TopSet::new(3, u32::gt);
+        assert_eq![ top.insert_is_best(10), (None, true) ];
+        assert_eq![ top.insert_is_best(20), (None, true) ];
+        assert_eq![ top.insert_is_best(30), (None, true) ];
+        assert_eq![ top.insert_is_best(40), (Some(10), true) ];
+        assert_eq![ top.insert_is_best(40), (Some(20), false) ];
+        assert_eq![ top.insert_is_best(35), (Some(30), false) ];
+        assert_eq![ top.insert_is_best(5), (Some(5), false) ];
+
+        let mut empty = TopSet::new(0, u32::gt);
+        assert_eq![ empty.insert_is_best(1), (Some(1), false) ];
+    }
+
+    #[test]
+    fn sane_comparators()
+    {
+        let items = (0..100).map(|i| (i * 37) % 101).collect::<Vec<u32>>();
+        TopSet::with_init(50, u32::gt, items.clone()).check_comparator_sanity();
+        TopSet::with_init(50, |a: &u32, b: &u32| a % 10 > b % 10, items).check_comparator_sanity();
+        TopSet::new(3, f64::gt).check_comparator_sanity();
+    }
+
+    #[test]
+    #[cfg_attr(debug_assertions, should_panic(expected = "not antisymmetric"))]
+    fn nan_mishandled()
+    {
+        // NaN is neither lower nor greater, so it beats and is beaten by everything
+        let beat = |a: &f64, b: &f64| a.partial_cmp(b).unwrap_or(core::cmp::Ordering::Greater).is_gt();
+        TopSet::with_init(4, beat, vec![1., f64::NAN, 3.]).check_comparator_sanity();
+    }
+
+    #[test]
+    #[cfg_attr(debug_assertions, should_panic(expected = "not transitive"))]
+    fn rock_paper_scissors()
+    {
+        let beat = |a: &u8, b: &u8| (a + 3 - b) % 3 == 1;
+        TopSet::with_init(3, beat, vec![0, 1, 2]).check_comparator_sanity();
+    }
+
+    #[test]
+    fn latest_among_equals()
+    {
+        // (entry, score)
+        let beat = |a: &(u32,u32), b: &(u32,u32)| a.1 > b.1;
+        let mut strict = TopSet::new(3, beat);
+        let mut inclusive = TopSet::new(3, beat);
+        for (i, score) in [5, 8, 5, 3, 5, 9, 5].into_iter().enumerate() {
+            let x = (i as u32, score);
+            assert_eq![ inclusive.is_candidate_inclusive(&x), !inclusive.is_full() || score >= 5 ];
+            strict.insert(x);
+            inclusive.insert_inclusive(x);
+            assert!( inclusive.is_valid_heap() );
+        }
+        assert_eq![ strict.into_sorted_vec(), vec![(2, 5), (1, 8), (5, 9)] ];
+        assert_eq![ inclusive.into_sorted_vec(), vec![(6, 5), (1, 8), (5, 9)] ];
+
+        let mut none = TopSet::new(0, beat);
+        assert!( !none.is_candidate_inclusive(&(0, 1)) );
+        assert_eq![ none.insert_inclusive((0, 1)), Some((0, 1)) ];
+    }
+
+    #[test]
+    fn split_into_parts()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        let top = TopSet::with_init(5, i32::gt, items.clone());
+        let expected = top.clone().into_sorted_vec();
+
+        // the buffer is given as a heap and taken back as is
+        let (heap, count, beat) = top.into_parts();
+        assert_eq![ heap.len(), 5 ];
+        assert_eq![ heap.iter().min(), heap.first() ];
+        let top = TopSet::from_parts(heap, count, beat);
+        assert!( top.is_valid_heap() );
+        assert_eq![ top.capacity(), 5 ];
+        assert_eq![ top.into_sorted_vec(), expected ];
+
+        // an arbitrary buffer is reordered and truncated
+        let top = TopSet::from_parts(items, 3, i32::lt);
+        assert!( top.is_valid_heap() );
+        assert_eq![ top.into_sorted_vec(), vec![1, 1, 0] ];
+    }
+
+    #[test]
+    fn secondary_tiebreak()
+    {
+        // (score, name): all the permutations lead to the same top set
+        let items = [(3,'d'), (1,'a'), (3,'b'), (3,'c'), (2,'e'), (3,'a')];
+        let by_score = |a: &(u32,char), b: &(u32,char)| a.0 > b.0;
+        let by_name = |a: &(u32,char), b: &(u32,char)| a.1 < b.1;
+        for rotation in 0..items.len() {
+            let mut rotated = items;
+            rotated.rotate_left(rotation);
+            let mut top = TopSet::new_with_tiebreak(3, by_score, by_name);
+            top.extend(rotated);
+            assert_eq![ top.into_sorted_vec(), vec![(3,'c'), (3,'b'), (3,'a')] ];
+        }
+
+        // the composed order is total: exactly one of two distinct items wins
+        let top = TopSet::new_with_tiebreak(3, by_score, by_name);
+        for a in &items {
+            for b in &items {
+                assert_eq![ top.beat(a, b) as u8 + top.beat(b, a) as u8, (a != b) as u8 ];
+            }
+        }
+    }
+
+    #[test]
+    fn merged_sorted_streams()
+    {
+        let mut state = 0x9E3779B97F4A7C15u64;
+        let mut random = move |bound: u64| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state % bound
+        };
+
+        for n in [0, 1, 3, 10, 50] {
+            for _ in 0..20 {
+                let streams = (0..random(6)).map(|_| {
+                    let mut stream = (0..random(40)).map(|_| random(100)).collect::<Vec<_>>();
+                    stream.sort_unstable_by(|a, b| b.cmp(a));
+                    stream
+                }).collect::<Vec<_>>();
+
+                // count the items read from the streams
+                let read = std::cell::Cell::new(0usize);
+                let counted = streams.iter().map(|stream| stream.iter().inspect(|_| read.set(read.get()+1)).copied());
+                let top = TopSet::from_sorted_streams(n, u64::gt, counted);
+                assert!( read.get() <= streams.iter().map(|s| s.len().min(n+1)).sum() );
+
+                let expected = TopSet::with_init(n, u64::gt, streams.concat());
+                assert_eq![ top.into_sorted_vec(), expected.into_sorted_vec() ];
+            }
+        }
+    }
+
+    #[test]
+    fn lazily_descending()
+    {
+        // the challenges are counted
+        let duels = std::cell::Cell::new(0);
+        let beat = |a: &u64, b: &u64| { duels.set(duels.get()+1); a > b };
+        let items = (0..5000).map(|i| (i * 7919) % 5003).collect::<Vec<u64>>();
+        let top = TopSet::with_init(1000, beat, items.clone());
+
+        let mut expected = top.clone().into_sorted_vec();
+        expected.reverse();
+        duels.set(0);
+        let mut iter = top.clone().into_iter_desc();
+        assert_eq![ iter.size_hint(), (1000, Some(1000)) ];
+        assert_eq![ iter.by_ref().take(3).collect::<Vec<_>>(), expected[..3] ];
+        assert_eq![ iter.size_hint(), (997, Some(997)) ];
+        // far less than a full sort (about 20000 duels)
+        assert!( duels.get() < 5000, "{} duels", duels.get() );
+        assert_eq![ top.into_iter_desc().collect::<Vec<_>>(), expected ];
+
+        // the tied items are in the reversed order too
+        let by_score = |a: &(u32,char), b: &(u32,char)| a.0 > b.0;
+        for mut top in [TopSet::new_deterministic(3, by_score), TopSet::new_fifo(3, by_score)] {
+            top.extend(vec![(1,'a'), (3,'b'), (3,'c'), (2,'d'), (3,'e')]);
+            let mut expected = top.clone().into_sorted_vec();
+            expected.reverse();
+            assert_eq![ top.into_iter_desc().collect::<Vec<_>>(), expected ];
+        }
+
+        // otherwise, only the scores are ordered
+        let top = TopSet::with_init(3, by_score, vec![(1,'a'), (3,'b'), (3,'c'), (2,'d'), (3,'e')]);
+        assert_eq![ top.into_iter_desc().map(|x| x.0).collect::<Vec<_>>(), vec![3, 3, 3] ];
+    }
+
+    #[test]
+    fn removed_anywhere()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        let top = TopSet::with_init(10, i32::gt, items.clone());
+        let stored = top.clone().into_sorted_vec();
+
+        // the root, the leaves and everything in between
+        for x in &stored {
+            let mut top = top.clone();
+            assert!( top.remove(x) );
+            assert!( top.is_valid_heap() );
+            let mut expected = stored.clone();
+            expected.remove(expected.iter().position(|e| e == x).unwrap());
+            assert_eq![ top.into_sorted_vec(), expected ];
+        }
+
+        // until empty, from the greatest
+        let mut top = top;
+        for x in stored.iter().rev() {
+            assert!( top.remove(x) );
+            assert!( top.is_valid_heap() );
+        }
+        assert!( top.is_empty() );
+        assert!( !top.remove(&5) );
+
+        // an item going up after the removal
+        let mut top = TopSet::with_init(7, i32::gt, vec![1, 10, 2, 11, 12, 3, 4]);
+        assert!( top.remove(&11) );
+        assert!( top.is_valid_heap() );
+        assert_eq![ top.into_sorted_vec(), vec![1, 2, 3, 4, 10, 12] ];
+    }
+
+    #[test]
+    fn faster_than_some()
+    {
+        // lap times: the fastest ones are the best
+        let top = TopSet::with_init(10, u32::lt, vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0]);
+        assert_eq![ top.count_better_than(&5), 5 ];
+        assert_eq![ top.count_worse_than(&5), 1 ];
+        assert_eq![ top.count_worse_than(&13), 0 ];
+        assert_eq![ top.count_better_than(&13), 10 ];
+        assert_eq![ top.count_worse_than(&0), 9 ];
+        for x in 0..30 {
+            let ties = top.iter().filter(|&&e| e == x).count();
+            assert_eq![ top.count_better_than(&x) + top.count_worse_than(&x) + ties, top.len() ];
+        }
+        assert_eq![ top.len(), 10 ];
+    }
 }
\ No newline at end of file
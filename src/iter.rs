@@ -1,4 +1,10 @@
-use std::iter::{FusedIterator};
+use core::cmp::Ordering;
+use core::iter::{FusedIterator};
+use core::time::Duration;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use crate::heap::ordering_beat;
+use crate::leaderboard::leaderboard_beat;
 use crate::TopSet;
 
 pub struct IntoIterSorted<X,C>(TopSet<X,C>)
@@ -28,6 +34,13 @@ impl<X,C> Iterator for IntoIterSorted<X,C>
     }
 }
 
+impl<X,C> DoubleEndedIterator for IntoIterSorted<X,C>
+    where C: Fn(&X,&X) -> bool
+{
+    // the greatest item is searched among the leaves of the heap
+    #[inline] fn next_back(&mut self) -> Option<Self::Item> { self.0.pop_best() }
+}
+
 impl<X,C:Fn(&X,&X)->bool> FusedIterator for IntoIterSorted<X,C> { }
 
 // impl<X,C:Fn(&X,&X)->bool> TrustedLen for IntoIterSorted<X,C> { }
@@ -40,14 +53,121 @@ impl<X,C> ExactSizeIterator for IntoIterSorted<X,C>
     // #[inline] fn is_empty(&self) -> bool { self.0.is_empty() }
 }
 
+pub struct DrainSorted<'a,X,C>(&'a mut TopSet<X,C>)
+    where C: Fn(&X,&X) -> bool;
+
+impl<'a,X,C> From<&'a mut TopSet<X,C>> for DrainSorted<'a,X,C>
+    where C: Fn(&X,&X) -> bool
+{
+    #[inline] fn from(topset: &'a mut TopSet<X, C>) -> Self { Self(topset) }
+}
+
+impl<X,C> Iterator for DrainSorted<'_,X,C>
+    where C: Fn(&X,&X) -> bool
+{
+    type Item = X;
+    #[inline] fn next(&mut self) -> Option<Self::Item> { self.0.pop() }
+    #[inline] fn size_hint(&self) -> (usize, Option<usize>) { (self.0.len(), Some(self.0.len())) }
+}
+
+impl<X,C> Drop for DrainSorted<'_,X,C>
+    where C: Fn(&X,&X) -> bool
+{
+    // the remaining items are dropped in order
+    fn drop(&mut self) { self.for_each(drop) }
+}
+
+impl<X,C:Fn(&X,&X)->bool> FusedIterator for DrainSorted<'_,X,C> { }
+
+impl<X,C> ExactSizeIterator for DrainSorted<'_,X,C>
+    where C: Fn(&X,&X) -> bool
+{
+    #[inline] fn len(&self) -> usize { self.0.len() }
+}
+
 pub trait TopSetReducing
 {
     type Item;
 
     /// Build the top set according to the specified challenge.
+    ///
+    /// For floats, see [`TopSet::new`] about `NaN` (e.g. use [`crate::greatest_f64`]
+    /// as challenge rather than `f64::gt`).
     fn topset<C>(self, n: usize, beat: C) -> TopSet<Self::Item, C>
         where C: Fn(&Self::Item, &Self::Item) -> bool;
 
+    /// Build the top set according to the specified challenge with periodic snapshots.
+    ///
+    /// The closure `on_snapshot` is called with the current top set after every `every`
+    /// insertions and once at the end (unless the last insertion has just triggered it).
+    /// If `every` is `0`, only the final snapshot is done.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSetReducing;
+    /// let mut snapshots = vec![];
+    /// let top = (1..=10).topset_snapshots(2, u32::gt, 4, |top| snapshots.push(top.peek().cloned()));
+    /// assert_eq!( snapshots, vec![Some(3), Some(7), Some(9)]);
+    /// ```
+    fn topset_snapshots<C,F>(self, n: usize, beat: C, every: usize, on_snapshot: F) -> TopSet<Self::Item, C>
+        where C: Fn(&Self::Item, &Self::Item) -> bool, F: FnMut(&TopSet<Self::Item, C>);
+
+    /// Scans the items, yielding the sorted top set after each insertion.
+    ///
+    /// For each item, the current top set is cloned and sorted (from the
+    /// _lowest_ to the _greatest_), which costs `O(n log n)` per step:
+    /// this is intended to follow how the top set evolves on short inputs.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSetReducing;
+    /// let steps = vec![3, 1, 4, 1, 5].topset_scan(2, u32::gt).collect::<Vec<_>>();
+    /// assert_eq!( steps, vec![vec![3], vec![1,3], vec![3,4], vec![3,4], vec![4,5]]);
+    /// ```
+    fn topset_scan<C>(self, n: usize, beat: C) -> impl Iterator<Item=Vec<Self::Item>>
+        where C: Fn(&Self::Item, &Self::Item) -> bool, Self::Item: Clone;
+
+    /// Scans the items, yielding the sorted top set of the last `w` items after each insertion.
+    ///
+    /// At each step, the yielded vector contains the `n` greatest items (according to `beat`)
+    /// among the last `w` ones, sorted from the _lowest_ to the _greatest_.
+    ///
+    /// The window is retained, so the memory cost is `w` clones of the items
+    /// (in addition to the top set). Each step costs `O(log n)` to update the top set
+    /// and `O(n log n)` to yield it; when a kept item leaves the window, the top set
+    /// is rebuilt from the window, which costs `O(w log n)`.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSetReducing;
+    /// let steps = vec![3, 1, 4, 1, 5, 0, 2].topset_windowed(2, 3, u32::gt).collect::<Vec<_>>();
+    /// assert_eq!( steps, vec![vec![3], vec![1,3], vec![3,4], vec![1,4], vec![4,5], vec![1,5], vec![2,5]]);
+    /// ```
+    fn topset_windowed<C>(self, n: usize, w: usize, beat: C) -> impl Iterator<Item=Vec<Self::Item>>
+        where C: Fn(&Self::Item, &Self::Item) -> bool, Self::Item: Clone;
+
+    /// Build the top set of a fraction of the items.
+    ///
+    /// The capacity is the number of items times `fraction`, rounded up
+    /// (so `1.0` keeps all the items and any positive fraction keeps at least one).
+    /// If the exact number of items is known in advance (see [`ExactSizeIterator`]),
+    /// the items are inserted on the fly. Otherwise, they are first buffered
+    /// and the selection is then done in linear time (see [`TopSet::rebuild`]).
+    ///
+    /// # Panics
+    /// If `fraction` is not in the range `[0.0, 1.0]`.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSetReducing;
+    /// let top = (1..=10).topset_percent(0.25, u32::gt);
+    /// assert_eq!( top.into_sorted_vec(), vec![8,9,10]);
+    /// let top = (1..=10).filter(|x| x % 2 == 0).topset_percent(0.5, u32::gt);
+    /// assert_eq!( top.into_sorted_vec(), vec![6,8,10]);
+    /// ```
+    fn topset_percent<C>(self, fraction: f64, beat: C) -> TopSet<Self::Item, C>
+        where C: Fn(&Self::Item, &Self::Item) -> bool;
+
     /// Build the top set of the greatest values.
     #[inline]
     #[allow(clippy::type_complexity)]
@@ -65,8 +185,115 @@ pub trait TopSetReducing
     {
         self.topset(n, <Self::Item as PartialOrd>::lt)
     }
+
+    /// Build the top set of the items with the greatest keys.
+    ///
+    /// The key is computed by `key` at each duel (so it should be cheap)
+    /// and the keys should be totally ordered.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSetReducing;
+    /// let files = vec![("a.txt", 120), ("b.bin", 4096), ("c.rs", 830), ("d.md", 55)];
+    /// let top = files.topset_by_key(2, |f| f.1).into_sorted_vec();
+    /// assert_eq!( top, vec![("c.rs", 830), ("b.bin", 4096)]);
+    /// ```
+    #[inline]
+    fn topset_by_key<K,F>(self, n: usize, key: F) -> TopSet<Self::Item, impl Fn(&Self::Item,&Self::Item) -> bool>
+        where K: PartialOrd, F: Fn(&Self::Item) -> K, Self: Sized
+    {
+        self.topset(n, move |a, b| key(a) > key(b))
+    }
+
+    /// Build the top set of the items with the lowest keys.
+    ///
+    /// See [`Self::topset_by_key`].
+    #[inline]
+    fn topset_lowest_by_key<K,F>(self, n: usize, key: F) -> TopSet<Self::Item, impl Fn(&Self::Item,&Self::Item) -> bool>
+        where K: PartialOrd, F: Fn(&Self::Item) -> K, Self: Sized
+    {
+        self.topset(n, move |a, b| key(a) < key(b))
+    }
+
+    /// Build the top set of the greatest items according to a comparison function.
+    ///
+    /// See [`TopSet::new_by`].
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSetReducing;
+    /// let top = vec![7,5,6,9,4,2,3].topset_by_ordering(2, u32::cmp);
+    /// assert_eq!( top.into_sorted_vec(), vec![7,9]);
+    /// ```
+    #[inline]
+    fn topset_by_ordering<F>(self, n: usize, cmp: F) -> TopSet<Self::Item, impl Fn(&Self::Item,&Self::Item) -> bool>
+        where F: Fn(&Self::Item,&Self::Item) -> Ordering, Self: Sized
+    {
+        self.topset(n, ordering_beat(cmp))
+    }
+
+    /// Build the top set of a leaderboard.
+    ///
+    /// The items with the higher `score` are kept and, on equal scores,
+    /// the most recent ones (see [`leaderboard_beat`]).
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSetReducing;
+    /// // (score, submission time)
+    /// let top = vec![(10,1), (12,2), (10,3), (9,4)].leaderboard(2, |x| x.0, |x| x.1);
+    /// assert_eq!( top.into_sorted_vec(), vec![(10,3), (12,2)]);
+    /// ```
+    #[inline]
+    fn leaderboard<S,T,FS,FT>(self, n: usize, score: FS, time: FT) -> TopSet<Self::Item, impl Fn(&Self::Item,&Self::Item) -> bool>
+        where S: PartialOrd, T: Ord, FS: Fn(&Self::Item) -> S, FT: Fn(&Self::Item) -> T, Self: Sized
+    {
+        self.topset(n, leaderboard_beat(score, time))
+    }
+
+    /// Build the top set of the greatest byte strings (in lexicographic order).
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSetReducing;
+    /// let keys: Vec<Vec<u8>> = vec![b"row:10".to_vec(), b"row:9".to_vec(), b"row:100".to_vec(), b"col".to_vec()];
+    /// let top = keys.clone().topset_greatest_bytes(2).into_sorted_vec();
+    /// assert_eq!( top, vec![b"row:100".to_vec(), b"row:9".to_vec()]);
+    /// let low = keys.topset_lowest_bytes(2).into_sorted_vec();
+    /// assert_eq!( low, vec![b"row:10".to_vec(), b"col".to_vec()]);
+    /// ```
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    fn topset_greatest_bytes(self, n: usize) -> TopSet<Self::Item, fn(&Self::Item,&Self::Item)->bool>
+        where Self::Item: AsRef<[u8]>, Self: Sized
+    {
+        self.topset(n, gt_bytes::<Self::Item>)
+    }
+
+    /// Build the top set of the lowest byte strings (in lexicographic order).
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    fn topset_lowest_bytes(self, n: usize) -> TopSet<Self::Item, fn(&Self::Item,&Self::Item)->bool>
+        where Self::Item: AsRef<[u8]>, Self: Sized
+    {
+        self.topset(n, lt_bytes::<Self::Item>)
+    }
+}
+
+// internal stuff
+// the number of items in a fraction of `len`, rounded up (f64::ceil is not available without std)
+fn fraction_of(len: usize, fraction: f64) -> usize
+{
+    let exact = len as f64 * fraction;
+    let n = exact as usize;
+    if (n as f64) < exact { n+1 } else { n }.min(len)
 }
 
+// internal stuff
+// lexicographic comparisons of byte strings
+fn gt_bytes<T: AsRef<[u8]>>(a: &T, b: &T) -> bool { a.as_ref() > b.as_ref() }
+fn lt_bytes<T: AsRef<[u8]>>(a: &T, b: &T) -> bool { a.as_ref() < b.as_ref() }
+
 impl<I:IntoIterator> TopSetReducing for I
 {
     type Item = I::Item;
@@ -77,12 +304,118 @@ impl<I:IntoIterator> TopSetReducing for I
     {
         self.into_iter().fold(TopSet::new(n,beat), |mut top, e| { top.insert(e); top })
     }
+
+    fn topset_percent<C>(self, fraction: f64, beat: C) -> TopSet<Self::Item, C>
+        where C: Fn(&Self::Item, &Self::Item) -> bool
+    {
+        assert!((0. ..=1.).contains(&fraction), "the fraction should be in [0, 1]");
+        let iter = self.into_iter();
+        match iter.size_hint() {
+            (lower, Some(upper)) if lower == upper => {
+                let mut top = TopSet::new(fraction_of(lower, fraction), beat);
+                top.extend(iter);
+                top
+            }
+            _ => {
+                let data = iter.collect::<Vec<_>>();
+                TopSet::rebuild(fraction_of(data.len(), fraction), beat, data)
+            }
+        }
+    }
+
+    fn topset_scan<C>(self, n: usize, beat: C) -> impl Iterator<Item=Vec<Self::Item>>
+        where C: Fn(&Self::Item, &Self::Item) -> bool, Self::Item: Clone
+    {
+        let mut top = TopSet::new(n, beat);
+        self.into_iter().map(move |e| {
+            top.insert(e);
+            top.to_sorted_vec()
+        })
+    }
+
+    fn topset_windowed<C>(self, n: usize, w: usize, beat: C) -> impl Iterator<Item=Vec<Self::Item>>
+        where C: Fn(&Self::Item, &Self::Item) -> bool, Self::Item: Clone
+    {
+        // the items are numbered to know if an expiring one is kept
+        let mut top = TopSet::new(n, move |a: &(usize,Self::Item), b: &(usize,Self::Item)| beat(&a.1, &b.1));
+        let mut window = VecDeque::new();
+        self.into_iter().enumerate().map(move |(i, e)| {
+            window.push_back((i, e.clone()));
+            top.insert((i, e));
+            if window.len() > w {
+                if let Some((expired, _)) = window.pop_front() {
+                    if top.iter().any(|x| x.0 == expired) {
+                        top.refill(window.iter().cloned());
+                    }
+                }
+            }
+            top.to_sorted_vec().into_iter().map(|x| x.1).collect()
+        })
+    }
+
+    fn topset_snapshots<C,F>(self, n: usize, beat: C, every: usize, mut on_snapshot: F) -> TopSet<Self::Item, C>
+        where C: Fn(&Self::Item, &Self::Item) -> bool, F: FnMut(&TopSet<Self::Item, C>)
+    {
+        let mut top = TopSet::new(n, beat);
+        let mut pending = true;
+        for (i, e) in self.into_iter().enumerate() {
+            top.insert(e);
+            pending = every == 0 || (i+1) % every != 0;
+            if !pending {
+                on_snapshot(&top);
+            }
+        }
+        if pending {
+            on_snapshot(&top);
+        }
+        top
+    }
+}
+
+/// Extension of iterators over durations, named for latency monitoring.
+///
+/// # Example
+/// ```
+/// # use std::time::Duration;
+/// # use topset::iter::DurationTopSetReducing;
+/// let latencies = [12, 250, 30, 180, 5, 90].map(Duration::from_millis);
+/// let slowest = latencies.topset_slowest(3).into_sorted_vec();
+/// assert_eq!( slowest, [90, 180, 250].map(Duration::from_millis));
+/// let fastest = latencies.topset_fastest(2).into_sorted_vec();
+/// assert_eq!( fastest, [12, 5].map(Duration::from_millis));
+/// ```
+pub trait DurationTopSetReducing
+{
+    /// Build the top set of the slowest durations (the greatest ones).
+    #[allow(clippy::type_complexity)]
+    fn topset_slowest(self, n: usize) -> TopSet<Duration, fn(&Duration,&Duration)->bool>;
+
+    /// Build the top set of the fastest durations (the lowest ones).
+    #[allow(clippy::type_complexity)]
+    fn topset_fastest(self, n: usize) -> TopSet<Duration, fn(&Duration,&Duration)->bool>;
+}
+
+impl<I:IntoIterator<Item=Duration>> DurationTopSetReducing for I
+{
+    #[inline]
+    fn topset_slowest(self, n: usize) -> TopSet<Duration, fn(&Duration,&Duration)->bool>
+    {
+        self.topset_greatest(n)
+    }
+
+    #[inline]
+    fn topset_fastest(self, n: usize) -> TopSet<Duration, fn(&Duration,&Duration)->bool>
+    {
+        self.topset_lowest(n)
+    }
 }
 
 
 #[cfg(test)]
 mod tests {
+    use alloc::vec::Vec;
     use crate::iter::TopSetReducing;
+    use crate::TopSet;
 
     #[test]
     fn lowest_cost()
@@ -99,6 +432,17 @@ mod tests {
         assert_eq![top.pop(), None];
     }
 
+    #[test]
+    fn keyed_selection()
+    {
+        let words = vec!["kiwi", "fig", "banana", "cherries", "apple", "plum"];
+        let longest = words.clone().topset_by_key(2, |w| w.len());
+        assert_eq![ longest.into_sorted_vec(), vec!["banana", "cherries"] ];
+        let shortest = words.topset_lowest_by_key(3, |w| w.len());
+        assert_eq![ shortest.peek().map(|w| w.len()), Some(4) ];
+        assert_eq![ shortest.into_sorted_vec().last(), Some(&"fig") ];
+    }
+
     #[test]
     fn greatest_score()
     {
@@ -110,6 +454,52 @@ mod tests {
             Some(877)];
     }
 
+    #[test]
+    fn snapshots()
+    {
+        let count = |every| {
+            let mut calls = 0;
+            (0..10).topset_snapshots(3, i32::gt, every, |_| calls += 1);
+            calls
+        };
+        assert_eq!( count(3), 4 );
+        assert_eq!( count(5), 2 );
+        assert_eq!( count(1), 10 );
+        assert_eq!( count(0), 1 );
+
+        let mut lens = vec![];
+        let top = Vec::<i32>::new().topset_snapshots(3, i32::gt, 2, |top| lens.push(top.len()));
+        assert!( top.is_empty() );
+        assert_eq!( lens, vec![0] );
+    }
+
+    #[test]
+    fn windowed_matches_brute_force()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0, 3, 60, 2];
+        for n in [0, 1, 3, 20] {
+            for w in [0, 1, 2, 5, 30, usize::MAX] {
+                let expected = (0..items.len()).map(|i| {
+                    let window = &items[(i+1).saturating_sub(w)..=i];
+                    TopSet::with_init(n, u32::gt, window.iter().copied()).into_sorted_vec()
+                }).collect::<Vec<_>>();
+                assert_eq!( items.iter().copied().topset_windowed(n, w, u32::gt).collect::<Vec<_>>(), expected );
+            }
+        }
+    }
+
+    #[test]
+    fn scan_matches_steps()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        let mut top = TopSet::new(3, u32::lt);
+        let expected = items.iter().map(|&x| {
+            top.insert(x);
+            top.clone().into_sorted_vec()
+        }).collect::<Vec<_>>();
+        assert_eq!( items.topset_scan(3, u32::lt).collect::<Vec<_>>(), expected );
+    }
+
     #[test]
     fn iterator()
     {
@@ -124,4 +514,69 @@ mod tests {
         assert_eq!( iter.size_hint(), (3, Some(3)));
         assert_eq!( iter.last(), Some(1));
     }
+
+    #[test]
+    fn percentage()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        assert_eq!( items.clone().topset_percent(0.2, i32::gt).into_sorted_vec(), vec![81, 97, 877] );
+        assert_eq!( items.clone().topset_percent(0.21, i32::gt).len(), 4 );
+        assert_eq!( items.clone().topset_percent(1., i32::gt).len(), 15 );
+        assert_eq!( items.clone().topset_percent(0.01, i32::gt).into_sorted_vec(), vec![877] );
+        assert!( items.clone().topset_percent(0., i32::gt).is_empty() );
+
+        // the length is unknown in advance
+        let unknown = items.iter().copied().filter(|&x| x > 4);
+        assert_eq!( unknown.topset_percent(0.5, i32::gt).into_sorted_vec(), vec![22, 45, 81, 97, 877] );
+        assert!( std::iter::empty::<i32>().topset_percent(0.5, i32::gt).is_empty() );
+    }
+
+    #[test]
+    #[should_panic]
+    fn percentage_out_of_range()
+    {
+        (1..10).topset_percent(1.5, i32::gt);
+    }
+
+    #[test]
+    fn both_ends()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        for n in 0..=items.len() {
+            let top = items.clone().topset_greatest(n);
+            let sorted = top.clone().into_sorted_vec();
+            assert_eq!( top.clone().into_iter_sorted().rev().collect::<Vec<_>>(), sorted.iter().rev().copied().collect::<Vec<_>>() );
+
+            // interleaved from both ends
+            let mut iter = top.into_iter_sorted();
+            let (mut front, mut back) = (vec![], vec![]);
+            for step in 0.. {
+                assert_eq!( iter.len(), n - front.len() - back.len() );
+                let next = if step % 3 == 0 { iter.next().map(|x| front.push(x)) } else { iter.next_back().map(|x| back.push(x)) };
+                if next.is_none() { break; }
+            }
+            back.reverse();
+            front.extend(back);
+            assert_eq!( front, sorted );
+        }
+    }
+
+    #[test]
+    fn both_ends_with_ties()
+    {
+        let by_score = |a: &(u32,u32), b: &(u32,u32)| a.0 > b.0;
+        let items = (0..40).map(|i| (i % 3, i)).collect::<Vec<_>>();
+        for mut top in [TopSet::new_deterministic(20, by_score), TopSet::new_fifo(20, by_score)] {
+            top.extend(items.clone());
+            let sorted = top.clone().into_sorted_vec();
+            assert_eq!( top.clone().into_iter_sorted().rev().collect::<Vec<_>>(), sorted.iter().rev().copied().collect::<Vec<_>>() );
+
+            let mut iter = top.into_iter_sorted();
+            let mut back = iter.by_ref().rev().take(5).collect::<Vec<_>>();
+            back.reverse();
+            let mut front = iter.collect::<Vec<_>>();
+            front.extend(back);
+            assert_eq!( front, sorted );
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,48 @@
+//! Ready-made challenges for leaderboards.
+use core::cmp::Ordering;
+
+/// Builds the usual challenge of a leaderboard.
+///
+/// The item with the higher `score` wins. On equal scores, the most recent
+/// item (with the greater `time`) wins. If the scores are not comparable
+/// (e.g. a NaN), no item wins.
+///
+/// # Example
+/// ```
+/// # use topset::TopSet;
+/// # use topset::leaderboard::leaderboard_beat;
+/// // (score, submission time)
+/// let beat = leaderboard_beat(|x: &(u32,u64)| x.0, |x: &(u32,u64)| x.1);
+/// assert!( beat(&(10, 1), &(8, 2)) );
+/// assert!( beat(&(10, 3), &(10, 2)) );
+/// assert!( !beat(&(10, 2), &(10, 2)) );
+/// ```
+pub fn leaderboard_beat<X,S,T>(score: impl Fn(&X) -> S, time: impl Fn(&X) -> T) -> impl Fn(&X,&X) -> bool
+    where S: PartialOrd, T: Ord
+{
+    move |a, b| match score(a).partial_cmp(&score(b)) {
+        Some(Ordering::Greater) => true,
+        Some(Ordering::Equal) => time(a) > time(b),
+        _ => false
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use crate::iter::TopSetReducing;
+
+    #[test]
+    fn recency_decides_ties()
+    {
+        // (player, score, submission time)
+        let submissions = vec![
+            ("ann", 9.5, 1), ("bob", 7.0, 2), ("eve", 8.0, 3), ("joe", 8.0, 4),
+            ("kim", 7.0, 5), ("max", 8.0, 6), ("sam", 8.0, 5)
+        ];
+        let top = submissions.leaderboard(3, |s| s.1, |s| s.2);
+        let names = top.into_sorted_vec().into_iter().map(|s| s.0).collect::<Vec<_>>();
+        assert_eq![ names, vec!["sam", "max", "ann"] ];
+    }
+}
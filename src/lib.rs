@@ -34,11 +34,79 @@
 //! in the last 4: 3
 //! in the last 4: 1
 //! ```
+//!
+//! # Floats
+//! A challenge such as `f64::gt` is not a total ordering as soon as a `NaN` is
+//! inserted, which silently corrupts the top set. This cannot be checked by
+//! [`TopSet::new`] or [`TopSetReducing::topset`] (it would require specialization,
+//! which is not available on stable Rust). So, for floats, prefer:
+//! * [`TopSet::new_total_f64`] (or [`greatest_f64`] as challenge) to order the `NaN`s too;
+//! * [`TopSet::new_float`] to check, in debug builds, that no `NaN` is inserted.
+//!
+//! # Features
+//! * `std` (enabled by default): provides the methods which need the standard library
+//!   (e.g. the ones returning a `HashMap`). Without it, the crate is `no_std`
+//!   and only requires `alloc`.
+//! * `smallvec`: provides [`SmallTopSet`], a top set whose items are stored inline
+//!   (without allocation) as long as they fit in a fixed size array.
+//! * `serde`: provides the serialization of the items of a top set (the challenge is provided
+//!   back at deserialization).
+//! * `bincode` (implies `serde`): provides a compact binary encoding of the sorted items.
+//! * `json` (implies `serde`): provides a JSON export of the sorted items.
+//! * `rayon`: provides [`ParallelTopSetReducing`] to build a top set from a parallel iterator.
+//! * `bench-api`: provides the module [`bench`](mod@bench) with deterministic workloads and internal hooks
+//!   for benchmarking.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+extern crate alloc;
+#[cfg(all(test, not(feature = "std")))]
+#[macro_use]
+extern crate std;
 
+use alloc::vec::Vec;
+
+mod array;
+#[cfg(feature = "bench-api")]
+pub mod bench;
+mod builder;
+mod collect;
+mod dary;
+mod evict;
+mod fallible;
+mod float;
+#[cfg(feature = "std")]
+mod group;
 mod heap;
 pub mod iter;
+pub mod leaderboard;
+mod minmax;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod rolling;
+mod select;
+#[cfg(feature = "smallvec")]
+mod small;
+#[cfg(feature = "serde")]
+mod serialize;
 
-pub use iter::TopSetReducing;
+pub use array::ArrayTopSet;
+pub use builder::{NoHook, TieBreak, TopSetBuilder};
+pub use collect::{Greatest, Lowest};
+pub use dary::DaryTopSet;
+pub use evict::EvictingTopSet;
+pub use fallible::TryTopSet;
+pub use float::{MaybeNan, greatest_f32, greatest_f64, lowest_f32, lowest_f64};
+#[cfg(feature = "std")]
+pub use group::group_topset;
+pub use heap::{BoxedTopSet, PeekMut};
+pub use iter::{DurationTopSetReducing, TopSetReducing};
+pub use minmax::MinMaxSet;
+#[cfg(feature = "rayon")]
+pub use parallel::ParallelTopSetReducing;
+pub use rolling::RollingTopSet;
+pub use select::{build_scoped, kth_largest, nlargest, nlargest_by_key, nsmallest, nsmallest_by_key};
+#[cfg(feature = "smallvec")]
+pub use small::SmallTopSet;
 
 /// A top N set of items.
 ///
@@ -55,14 +123,29 @@ pub use iter::TopSetReducing;
 ///
 /// Of course, any closure could be used but it should satisfy the transitivity.
 /// In other words, if `a` beats `b` and `b` beats `c` then `a` should beat `c` too.
-/// If it is not the case, the results are unpredictable.
+/// If it is not the case, the results are unpredictable (in debug builds,
+/// [`TopSet::check_comparator_sanity`] helps to detect it).
+///
+/// A top set is [`Clone`] as long as its challenge is. Otherwise (e.g. for a closure
+/// capturing a non-cloneable state), the items could be duplicated with [`TopSet::clone_with`].
+///
+/// Likewise, a top set is [`Send`] (resp. [`Sync`]) as soon as its items and
+/// its challenge are, and so are its iterators (e.g. [`iter::IntoIterSorted`]).
+/// A plain function (e.g. `u32::gt`) or a closure capturing nothing is both,
+/// but a closure capturing an `Rc` or a `Cell` is not. A [`BoxedTopSet`] is only `Send`.
 ///
 #[derive(Clone)]
 pub struct TopSet<X,C>
     where C: Fn(&X,&X) -> bool
 {
     heap: Vec<X>, // a heap with the greatest at the end
+    stamps: Vec<u64>, // the insertion rank of each item of the heap (empty unless the ties are broken or the ages tracked)
+    seq: u64, // the number of insertions so far
+    tracked: bool, // the ages of the items are tracked (see `insert_tracked_age`)
     count: usize,
+    effective: usize, // the capacity lowered under memory pressure
+    ties: heap::Ties, // how the ties are broken (by the insertion rank)
+    dirty: bool, // the items were edited in place and the heap is not repaired
     beat:  C
 }
 
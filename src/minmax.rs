@@ -0,0 +1,201 @@
+use alloc::vec::Vec;
+use core::mem;
+use crate::heap::{duel_ordering, HeapBuffer};
+
+/// A set of both the `n` greatest and the `n` lowest items.
+///
+/// Each item is inserted once and feeds both extremes (with the same challenge).
+/// If fewer than `2n` items were inserted, the two extremes overlap but
+/// the shared items are counted once (see [`Self::into_sorted_vec`]).
+///
+/// # Example
+/// ```
+/// # use topset::MinMaxSet;
+/// let mut minmax = MinMaxSet::new(2, u32::gt);
+/// minmax.extend(vec![7,5,6,9,4,2,3]);
+/// assert_eq!( minmax.peek_min(), Some(&2));
+/// assert_eq!( minmax.peek_max(), Some(&9));
+/// assert_eq!( minmax.into_sorted_vec(), vec![2,3,7,9]);
+/// ```
+#[derive(Clone)]
+pub struct MinMaxSet<X,C>
+    where C: Fn(&X,&X) -> bool
+{
+    high: Vec<X>, // a heap with the lowest of the greatest items at the root
+    low: Vec<X>, // a heap with the greatest of the lowest items at the root
+    seen: usize, // the number of insertions so far
+    count: usize,
+    beat: C
+}
+
+impl<X,C> MinMaxSet<X,C>
+    where C: Fn(&X,&X) -> bool
+{
+    /// Creates a new set keeping the `n` greatest and the `n` lowest items.
+    ///
+    /// See [`crate::TopSet::new`] for the meaning of `beat`.
+    pub fn new(n: usize, beat: C) -> Self
+    {
+        Self {
+            high: Vec::with_capacity(n),
+            low: Vec::with_capacity(n),
+            seen: 0,
+            count: n,
+            beat
+        }
+    }
+
+    /// Check if the set is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.high.is_empty() }
+
+    /// Get the number of distinct stored items (at most `2n`).
+    #[inline]
+    pub fn len(&self) -> usize { self.seen.min(2*self.count) }
+
+    /// Get the number of items kept at each extreme
+    #[inline]
+    pub fn capacity(&self) -> usize { self.count }
+
+    /// Read access to the lowest item
+    ///
+    /// The lowest item is searched among the leaves of a heap, so this is in `O(n)`.
+    pub fn peek_min(&self) -> Option<&X>
+    {
+        self.low[self.low.len()/2..].iter()
+            .reduce(|best, x| if (self.beat)(best, x) { x } else { best })
+    }
+
+    /// Read access to the greatest item
+    ///
+    /// The greatest item is searched among the leaves of a heap, so this is in `O(n)`.
+    pub fn peek_max(&self) -> Option<&X>
+    {
+        self.high[self.high.len()/2..].iter()
+            .reduce(|best, x| if (self.beat)(x, best) { x } else { best })
+    }
+
+    /// Insert a new item at both extremes.
+    pub fn insert(&mut self, x: X)
+        where X: Clone
+    {
+        self.seen += 1;
+        Side { heap: &mut self.low, beat: &self.beat, lowest: true }.insert(x.clone(), self.count);
+        Side { heap: &mut self.high, beat: &self.beat, lowest: false }.insert(x, self.count);
+    }
+
+    /// Returns the stored items in a sorted vector.
+    ///
+    /// The first element of the vector is the _lowest_ item and the last one
+    /// is the _greatest_ one. The `n` lowest items are followed by the `n`
+    /// greatest ones except the items which were already counted among
+    /// the lowest ones (if fewer than `2n` items were inserted).
+    pub fn into_sorted_vec(self) -> Vec<X>
+    {
+        let overlap = (2*self.count).saturating_sub(self.seen).min(self.high.len());
+        let (mut low, mut high) = (self.low, self.high);
+        low.sort_by(|a,b| duel_ordering(&self.beat, a, b));
+        high.sort_by(|a,b| duel_ordering(&self.beat, a, b));
+        low.extend(high.into_iter().skip(overlap));
+        low
+    }
+
+    /// Removes all the elements
+    #[inline]
+    pub fn clear(&mut self)
+    {
+        self.high.clear();
+        self.low.clear();
+        self.seen = 0;
+    }
+}
+
+impl<X,C> Extend<X> for MinMaxSet<X,C>
+    where X: Clone, C: Fn(&X,&X) -> bool
+{
+    #[inline]
+    fn extend<T: IntoIterator<Item=X>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|x| self.insert(x))
+    }
+}
+
+// internal stuff
+// one extreme of the set, as a heap whose root is the first item to evict
+struct Side<'a,X,C>
+{
+    heap: &'a mut Vec<X>,
+    beat: &'a C,
+    lowest: bool // keeps the lowest items (so the greatest is evicted first)
+}
+
+impl<X,C> Side<'_,X,C>
+    where C: Fn(&X,&X) -> bool
+{
+    // checks if a wins against b on this side
+    #[inline]
+    fn wins(&self, a: &X, b: &X) -> bool
+    {
+        if self.lowest { (self.beat)(b,a) } else { (self.beat)(a,b) }
+    }
+
+    fn insert(&mut self, mut x: X, n: usize)
+    {
+        if self.heap.len() < n {
+            self.heap.push(x);
+            self.percolate_up(self.heap.len()-1);
+        } else if n != 0 && self.wins(&x, &self.heap[0]) {
+            mem::swap(&mut x, &mut self.heap[0]);
+            self.percolate_down(0);
+        }
+    }
+}
+
+impl<X,C> HeapBuffer for Side<'_,X,C>
+    where C: Fn(&X,&X) -> bool
+{
+    #[inline] fn size(&self) -> usize { self.heap.len() }
+    #[inline] fn duel(&self, i: usize, j: usize) -> bool { self.wins(&self.heap[i], &self.heap[j]) }
+    #[inline] fn exchange(&mut self, i: usize, j: usize) { self.heap.swap(i, j) }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{MinMaxSet, TopSet};
+
+    #[test]
+    fn both_extremes()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        let mut minmax = MinMaxSet::new(3, i32::gt);
+        minmax.extend(items.iter().copied());
+        assert_eq![ minmax.len(), 6 ];
+        assert_eq![ minmax.peek_min(), Some(&0) ];
+        assert_eq![ minmax.peek_max(), Some(&877) ];
+
+        let mut expected = TopSet::with_init(3, i32::lt, items.clone()).into_sorted_vec();
+        expected.reverse();
+        expected.extend(TopSet::with_init(3, i32::gt, items).into_sorted_vec());
+        assert_eq![ minmax.into_sorted_vec(), expected ];
+    }
+
+    #[test]
+    fn overlapping_extremes()
+    {
+        let mut minmax = MinMaxSet::new(3, i32::gt);
+        assert!( minmax.is_empty() );
+        assert_eq![ minmax.peek_max(), None ];
+
+        minmax.extend(vec![5, 2]);
+        assert_eq![ minmax.len(), 2 ];
+        assert_eq![ minmax.clone().into_sorted_vec(), vec![2, 5] ];
+
+        minmax.extend(vec![9, 2, 7]);
+        assert_eq![ minmax.len(), 5 ];
+        assert_eq![ minmax.peek_min(), Some(&2) ];
+        assert_eq![ minmax.clone().into_sorted_vec(), vec![2, 2, 5, 7, 9] ];
+
+        minmax.insert(1);
+        assert_eq![ minmax.into_sorted_vec(), vec![1, 2, 2, 5, 7, 9] ];
+    }
+}
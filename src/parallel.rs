@@ -0,0 +1,60 @@
+use rayon::iter::ParallelIterator;
+use crate::TopSet;
+
+/// Extension of the parallel iterators of [`rayon`].
+///
+/// # Example
+/// ```
+/// # use rayon::prelude::*;
+/// # use topset::ParallelTopSetReducing;
+/// let top = (0..100_000u32).into_par_iter().par_topset(3, u32::gt);
+/// assert_eq!( top.into_sorted_vec(), vec![99_997, 99_998, 99_999]);
+/// ```
+pub trait ParallelTopSetReducing: ParallelIterator
+{
+    /// Build the top set according to the specified challenge, in parallel.
+    ///
+    /// Each thread builds its own top set from its part of the items
+    /// and these top sets are then merged pairwise (see [`TopSet::absorb`]).
+    fn par_topset<C>(self, n: usize, beat: C) -> TopSet<Self::Item, C>
+        where C: Fn(&Self::Item, &Self::Item) -> bool + Sync + Send;
+}
+
+impl<I: ParallelIterator> ParallelTopSetReducing for I
+{
+    fn par_topset<C>(self, n: usize, beat: C) -> TopSet<Self::Item, C>
+        where C: Fn(&Self::Item, &Self::Item) -> bool + Sync + Send
+    {
+        let heap = self
+            .fold(|| TopSet::new(n, &beat), |mut top, x| { top.insert(x); top })
+            .reduce(|| TopSet::new(n, &beat), |mut top, other| { top.absorb(other); top })
+            .into_vec();
+        TopSet::rebuild(n, beat, heap)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rayon::prelude::*;
+    use crate::{ParallelTopSetReducing, TopSet};
+
+    #[test]
+    fn parallel_and_sequential()
+    {
+        // a shuffled vector (7919 is coprime with the length)
+        let items = (0..200_000u64).map(|i| (i * 7919) % 200_000).collect::<Vec<_>>();
+
+        let parallel = items.par_iter().copied().par_topset(50, u64::gt);
+        let sequential = TopSet::with_init(50, u64::gt, items.iter().copied());
+        assert_eq![ parallel.len(), 50 ];
+        assert_eq![ parallel.into_sorted_vec(), sequential.into_sorted_vec() ];
+
+        let parallel = items.par_iter().map(|x| x % 1000).par_topset(10, u64::lt);
+        assert_eq![ parallel.into_sorted_vec(), vec![0; 10] ];
+
+        let empty = Vec::<u64>::new().into_par_iter().par_topset(10, u64::gt);
+        assert!( empty.is_empty() );
+        assert_eq![ empty.capacity(), 10 ];
+    }
+}
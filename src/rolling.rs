@@ -0,0 +1,144 @@
+use alloc::collections::VecDeque;
+use crate::TopSet;
+
+/// A top set rolled over successive time windows.
+///
+/// Items are inserted into the _current_ top set. When the window ends,
+/// [`Self::roll`] finalizes the current top set, keeps it among the last `w`
+/// finalized windows and starts a fresh one (with the same settings: capacity,
+/// challenge and tie breaking).
+///
+/// # Example
+/// ```
+/// # use topset::RollingTopSet;
+/// let mut rolling = RollingTopSet::new(2, 3, u32::gt);
+/// rolling.extend(vec![7,5,6]);
+/// rolling.roll();
+/// rolling.extend(vec![1,2,3]);
+/// assert_eq!( rolling.current().peek(), Some(&2));
+/// assert_eq!( rolling.window(0).unwrap().peek(), Some(&6));
+/// assert!( rolling.window(1).is_none());
+/// ```
+#[derive(Clone)]
+pub struct RollingTopSet<X,C>
+    where C: Fn(&X,&X) -> bool
+{
+    current: TopSet<X,C>,
+    windows: VecDeque<TopSet<X,C>>, // the most recent at the front
+    width: usize
+}
+
+impl<X,C> RollingTopSet<X,C>
+    where C: Fn(&X,&X) -> bool + Clone
+{
+    /// Creates a new rolling top set.
+    ///
+    /// Each window keeps its `n` greatest items (according to `beat`)
+    /// and only the `w` last finalized windows are kept.
+    pub fn new(n: usize, w: usize, beat: C) -> Self
+    {
+        Self {
+            current: TopSet::new(n, beat),
+            windows: VecDeque::with_capacity(w),
+            width: w
+        }
+    }
+
+    /// Finalizes the current window and starts a new one.
+    ///
+    /// If there are already `w` finalized windows, the oldest one is dropped
+    /// from the ring and returned.
+    pub fn roll(&mut self) -> Option<TopSet<X,C>>
+    {
+        let fresh = self.current.empty_like();
+        let finalized = core::mem::replace(&mut self.current, fresh);
+        if self.width == 0 {
+            return Some(finalized);
+        }
+        let oldest = if self.windows.len() == self.width { self.windows.pop_back() } else { None };
+        self.windows.push_front(finalized);
+        oldest
+    }
+}
+
+impl<X,C> RollingTopSet<X,C>
+    where C: Fn(&X,&X) -> bool
+{
+    /// Insert a new item in the current window.
+    ///
+    /// See [`TopSet::insert`].
+    #[inline]
+    pub fn insert(&mut self, x: X) -> Option<X> { self.current.insert(x) }
+
+    /// Read access to the top set of the current window.
+    #[inline]
+    pub fn current(&self) -> &TopSet<X,C> { &self.current }
+
+    /// Read access to a finalized window.
+    ///
+    /// The most recent finalized window is at index `0`.
+    /// `None` is returned if this window was not finalized or
+    /// was dropped from the ring.
+    #[inline]
+    pub fn window(&self, i: usize) -> Option<&TopSet<X,C>> { self.windows.get(i) }
+
+    /// Iterate over the finalized windows, from the most recent to the oldest.
+    #[inline]
+    pub fn windows(&self) -> impl Iterator<Item=&TopSet<X,C>> { self.windows.iter() }
+
+    /// Get the maximum number of kept finalized windows.
+    #[inline]
+    pub fn width(&self) -> usize { self.width }
+}
+
+impl<X,C> Extend<X> for RollingTopSet<X,C>
+    where C: Fn(&X,&X) -> bool
+{
+    #[inline]
+    fn extend<T: IntoIterator<Item=X>>(&mut self, iter: T) {
+        self.current.extend(iter)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::VecDeque;
+    use crate::{RollingTopSet, TopSet};
+
+    #[test]
+    fn rolling_windows()
+    {
+        let mut rolling = RollingTopSet::new(2, 3, u32::gt);
+        for minute in 0..5 {
+            rolling.extend((0..10).map(|x| 100*minute + x));
+            let dropped = rolling.roll();
+            assert_eq![ dropped.is_some(), minute >= 3 ];
+        }
+        assert!( rolling.current().is_empty() );
+        assert_eq![ rolling.windows().count(), 3 ];
+        assert_eq![ rolling.window(0).unwrap().clone().into_sorted_vec(), vec![408, 409] ];
+        assert_eq![ rolling.window(2).unwrap().clone().into_sorted_vec(), vec![208, 209] ];
+        assert!( rolling.window(3).is_none() );
+
+        rolling.insert(1);
+        assert_eq![ rolling.current().peek(), Some(&1) ];
+    }
+
+    #[test]
+    fn rolled_with_same_settings()
+    {
+        let by_score = |a: &(u32,char), b: &(u32,char)| a.0 > b.0;
+        let mut current = TopSet::new_fifo(3, by_score);
+        current.set_effective_capacity(2);
+        let mut rolling = RollingTopSet { current, windows: VecDeque::new(), width: 2 };
+        for _ in 0..2 {
+            rolling.extend(vec![(1,'a'), (3,'b'), (3,'c'), (2,'d'), (3,'e')]);
+            rolling.roll();
+        }
+        for top in rolling.windows() {
+            assert_eq![ top.capacity(), 3 ];
+            assert_eq![ top.clone().into_sorted_vec(), vec![(3,'c'), (3,'e')] ];
+        }
+    }
+}
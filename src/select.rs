@@ -0,0 +1,207 @@
+use alloc::vec::Vec;
+use crate::TopSet;
+
+/// Selects the top `n` items of an iterator with a mutable challenge.
+///
+/// Unlike [`TopSet::new`], the closure `beat` may mutate its captured
+/// state (e.g. a counter or a cache borrowed with `&mut`). It is only
+/// borrowed for the duration of the selection and is never stored in
+/// a long-lived top set.
+///
+/// The returned vector is sorted: the _lowest_ selected item comes first
+/// and the _greatest_ one is the last.
+///
+/// # Example
+/// ```
+/// # use topset::build_scoped;
+/// let mut duels = 0;
+/// let top = build_scoped(3, vec![7,5,6,9,4,2,3], |a: &u32, b: &u32| { duels += 1; a > b });
+/// assert_eq!( top, vec![6,7,9] );
+/// assert!( duels > 0 );
+/// ```
+pub fn build_scoped<X, I, F>(n: usize, iter: I, beat: F) -> Vec<X>
+    where I: IntoIterator<Item=X>, F: FnMut(&X,&X) -> bool
+{
+    let mut top = TopSet::new_mut(n, beat);
+    top.extend(iter);
+    top.into_iter_sorted().collect()
+}
+
+/// Selects the `k`-th greatest item of an iterator.
+///
+/// This is the _lowest_ item of the top `k` items (according to `beat`),
+/// so `k = 1` gives the greatest one. If the iterator yields fewer than `k`
+/// items (or if `k` is `0`), `None` is returned.
+///
+/// # Example
+/// ```
+/// # use topset::kth_largest;
+/// assert_eq!( kth_largest(vec![7,5,6,9,4,2,3], 3, u32::gt), Some(6) );
+/// assert_eq!( kth_largest(vec![7,5,6,9,4,2,3], 1, u32::lt), Some(2) );
+/// assert_eq!( kth_largest(vec![7,5], 3, u32::gt), None );
+/// ```
+pub fn kth_largest<I, X, C>(iter: I, k: usize, beat: C) -> Option<X>
+    where I: IntoIterator<Item=X>, C: Fn(&X,&X) -> bool
+{
+    if k == 0 {
+        return None;
+    }
+    let mut top = TopSet::with_init(k, beat, iter);
+    if top.len() < k {
+        None
+    } else {
+        top.pop()
+    }
+}
+
+/// Selects the `k` greatest items of an iterator, as Python's `heapq.nlargest`.
+///
+/// The returned vector is sorted from the _greatest_ to the _lowest_ item.
+///
+/// # Example
+/// ```
+/// # use topset::nlargest;
+/// assert_eq!( nlargest(3, vec![7,5,6,9,4,2,3]), vec![9,7,6] );
+/// ```
+pub fn nlargest<I, T>(k: usize, iter: I) -> Vec<T>
+    where I: IntoIterator<Item=T>, T: Ord
+{
+    let mut top = TopSet::top(k);
+    top.extend(iter);
+    best_first(top.into_sorted_vec())
+}
+
+/// Selects the `k` lowest items of an iterator, as Python's `heapq.nsmallest`.
+///
+/// The returned vector is sorted from the _lowest_ to the _greatest_ item.
+///
+/// # Example
+/// ```
+/// # use topset::nsmallest;
+/// assert_eq!( nsmallest(3, vec![7,5,6,9,4,2,3]), vec![2,3,4] );
+/// ```
+pub fn nsmallest<I, T>(k: usize, iter: I) -> Vec<T>
+    where I: IntoIterator<Item=T>, T: Ord
+{
+    let mut top = TopSet::bottom(k);
+    top.extend(iter);
+    best_first(top.into_sorted_vec())
+}
+
+/// Selects the `k` items of an iterator with the greatest keys.
+///
+/// See [`nlargest`]. The key is computed at each duel (so it should be cheap).
+///
+/// # Example
+/// ```
+/// # use topset::nlargest_by_key;
+/// assert_eq!( nlargest_by_key(2, vec!["kiwi", "fig", "banana"], |w| w.len()), vec!["banana", "kiwi"] );
+/// ```
+pub fn nlargest_by_key<I, T, K, F>(k: usize, iter: I, key: F) -> Vec<T>
+    where I: IntoIterator<Item=T>, K: Ord, F: Fn(&T) -> K
+{
+    let top = TopSet::with_init(k, |a: &T, b: &T| key(a) > key(b), iter);
+    best_first(top.into_sorted_vec())
+}
+
+/// Selects the `k` items of an iterator with the lowest keys.
+///
+/// See [`nsmallest`]. The key is computed at each duel (so it should be cheap).
+///
+/// # Example
+/// ```
+/// # use topset::nsmallest_by_key;
+/// assert_eq!( nsmallest_by_key(2, vec!["kiwi", "fig", "banana"], |w| w.len()), vec!["fig", "kiwi"] );
+/// ```
+pub fn nsmallest_by_key<I, T, K, F>(k: usize, iter: I, key: F) -> Vec<T>
+    where I: IntoIterator<Item=T>, K: Ord, F: Fn(&T) -> K
+{
+    let top = TopSet::with_init(k, |a: &T, b: &T| key(a) < key(b), iter);
+    best_first(top.into_sorted_vec())
+}
+
+// internal stuff
+// a top set sorted vector starts with its lowest item (i.e. the worst one), so it is reversed
+fn best_first<T>(mut sorted: Vec<T>) -> Vec<T>
+{
+    sorted.reverse();
+    sorted
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+    use std::cell::Cell;
+    use std::collections::HashMap;
+    use crate::{build_scoped, kth_largest, nlargest, nlargest_by_key, nsmallest, nsmallest_by_key, TopSet};
+
+    #[test]
+    fn kth_item()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        assert_eq![ kth_largest(items.clone(), 1, i32::gt), Some(877) ];
+        assert_eq![ kth_largest(items.clone(), 5, i32::gt), Some(22) ];
+        assert_eq![ kth_largest(items.clone(), 15, i32::gt), Some(0) ];
+        assert_eq![ kth_largest(items.clone(), 16, i32::gt), None ];
+        assert_eq![ kth_largest(items.clone(), 3, i32::lt), Some(1) ];
+        assert_eq![ kth_largest(items, 0, i32::gt), None ];
+        // nothing is consumed (even from an unbounded iterator)
+        assert_eq![ kth_largest(0.., 0, i32::gt), None ];
+    }
+
+    #[test]
+    fn python_like()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+        let mut sorted = items.clone();
+        sorted.sort();
+        for k in [0, 1, 4, 15, 20] {
+            let k_max = k.min(items.len());
+            assert_eq![ nsmallest(k, items.clone()), sorted[..k_max] ];
+            assert_eq![ nlargest(k, items.clone()), sorted.iter().rev().take(k).copied().collect::<Vec<_>>() ];
+        }
+
+        let words = vec!["kiwi", "fig", "banana", "cherries", "apple", "plum"];
+        assert_eq![ nlargest_by_key(3, words.clone(), |w| w.len()), vec!["cherries", "banana", "apple"] ];
+        let shortest = nsmallest_by_key(2, words, |w| w.len());
+        assert_eq![ shortest.iter().map(|w| w.len()).collect::<Vec<_>>(), vec![3, 4] ];
+    }
+
+    #[test]
+    fn memoized_keys()
+    {
+        // the key of a word (its number of vowels) is computed once per word
+        let computed = Cell::new(0);
+        let mut cache = HashMap::new();
+        let mut top = TopSet::new_mut(3, |a: &&str, b: &&str| {
+            let mut key = |w: &str| *cache.entry(w.to_string()).or_insert_with(|| {
+                computed.set(computed.get() + 1);
+                w.chars().filter(|c| "aeiou".contains(*c)).count()
+            });
+            key(a) > key(b)
+        });
+        top.extend(["banana", "kiwi", "plum", "cherry", "avocado", "fig"]);
+        assert_eq![ top.insert("sequoia"), Some("kiwi") ];
+        assert_eq![ top.into_sorted_vec(), vec!["banana", "avocado", "sequoia"] ];
+        assert_eq![ computed.get(), 7 ];
+    }
+
+    #[test]
+    fn scoped_mutable_counter()
+    {
+        let mut duels = 0usize;
+        let top = build_scoped(4, vec![8, 1, 5, 3, 9, 2, 7], |a: &i32, b: &i32| {
+            duels += 1;
+            a < b
+        });
+        assert_eq![ top, vec![5, 3, 2, 1] ];
+        assert!( duels >= 6 );
+
+        // the counter is released once the selection is done
+        duels = 0;
+        assert_eq![ build_scoped(0, vec![1, 2], |a: &i32, b: &i32| { duels += 1; a > b }), Vec::<i32>::new() ];
+        assert_eq![ duels, 0 ];
+    }
+}
@@ -0,0 +1,202 @@
+#[cfg(feature = "bincode")]
+use serde::de::DeserializeOwned;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::TopSet;
+
+/// Only the items and the capacity are serialized (the challenge could not be).
+///
+/// A serialized top set is deserialized with [`TopSet::deserialize_with`].
+///
+/// # Example
+/// ```
+/// # use topset::TopSet;
+/// let topset = TopSet::with_init(3, u32::gt, vec![7,5,6,9,4,2,3]);
+/// let json = serde_json::to_string(&topset).unwrap();
+///
+/// let mut de = serde_json::Deserializer::from_str(&json);
+/// let decoded = TopSet::deserialize_with(&mut de, u32::gt).unwrap();
+/// assert_eq!( decoded.capacity(), 3);
+/// assert_eq!( decoded.into_sorted_vec(), vec![6,7,9]);
+/// ```
+impl<X,C> Serialize for TopSet<X,C>
+    where X: Serialize, C: Fn(&X,&X) -> bool
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        let mut state = serializer.serialize_struct("TopSet", 2)?;
+        state.serialize_field("heap", &self.heap)?;
+        state.serialize_field("count", &self.count)?;
+        state.end()
+    }
+}
+
+// internal stuff
+// the serialized part of a top set
+#[derive(Deserialize)]
+#[serde(rename = "TopSet")]
+struct Stored<X>
+{
+    heap: Vec<X>,
+    count: usize
+}
+
+impl<X,C> TopSet<X,C>
+    where C: Fn(&X,&X) -> bool
+{
+    /// Encodes the sorted items in a compact binary format.
+    ///
+    /// Only the items are encoded (neither the challenge nor the heap layout),
+    /// from the _greatest_ to the _lowest_, as a length-prefixed sequence
+    /// (using [`bincode`]).
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let topset = TopSet::with_init(3, u32::gt, vec![7,5,6,9,4,2,3]);
+    /// let bytes = topset.to_sorted_bytes().unwrap();
+    /// let decoded = TopSet::from_sorted_bytes(&bytes, 3, u32::gt).unwrap();
+    /// assert_eq!( decoded.into_sorted_vec(), vec![6,7,9]);
+    /// ```
+    #[cfg(feature = "bincode")]
+    pub fn to_sorted_bytes(&self) -> bincode::Result<Vec<u8>>
+        where X: Serialize + Clone
+    {
+        let mut sorted = self.to_sorted_vec();
+        sorted.reverse();
+        bincode::serialize(&sorted)
+    }
+
+    /// Decodes a top set encoded by [`Self::to_sorted_bytes`].
+    ///
+    /// The order of the decoded items is not trusted: the heap is rebuilt
+    /// (see [`Self::rebuild`]) and, if there are more than `n` items,
+    /// only the `n` greatest ones are kept.
+    #[cfg(feature = "bincode")]
+    pub fn from_sorted_bytes(bytes: &[u8], n: usize, beat: C) -> bincode::Result<Self>
+        where X: DeserializeOwned
+    {
+        Ok(Self::rebuild(n, beat, bincode::deserialize(bytes)?))
+    }
+
+    /// Creates a top set from arbitrary items, a capacity and a challenge.
+    ///
+    /// This is the constructor used to deserialize a top set: the order
+    /// of `heap` is not trusted (e.g. a hand-edited file) and the heap is
+    /// rebuilt as in [`Self::rebuild`]. The arguments are the ones of
+    /// [`Self::from_parts`], i.e. the serialized fields and the challenge.
+    pub fn from_heap_and_comparator(heap: Vec<X>, count: usize, beat: C) -> Self
+    {
+        Self::rebuild(count, beat, heap)
+    }
+
+    /// Deserializes a top set serialized with [`Serialize`].
+    ///
+    /// The challenge is not serialized so it should be provided again
+    /// (see [`Self::from_heap_and_comparator`]).
+    pub fn deserialize_with<'de,D>(deserializer: D, beat: C) -> Result<Self, D::Error>
+        where D: Deserializer<'de>, X: Deserialize<'de>
+    {
+        let stored = Stored::deserialize(deserializer)?;
+        Ok(Self::from_heap_and_comparator(stored.heap, stored.count, beat))
+    }
+
+    /// Exports the sorted items as a JSON array.
+    ///
+    /// The items are cloned and sorted from the _greatest_ to the _lowest_
+    /// (i.e. the best comes first), which is the expected order of a leaderboard.
+    /// The error is the one of [`serde_json::to_value`], if an item cannot be
+    /// represented in JSON.
+    ///
+    /// # Example
+    /// ```
+    /// # use topset::TopSet;
+    /// let topset = TopSet::with_init(3, u32::gt, vec![7,5,6,9,4,2,3]);
+    /// assert_eq!( topset.to_json_array().unwrap().to_string(), "[9,7,6]");
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn to_json_array(&self) -> serde_json::Result<serde_json::Value>
+        where X: Serialize + Clone
+    {
+        let mut sorted = self.to_sorted_vec();
+        sorted.reverse();
+        serde_json::to_value(sorted)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use crate::TopSet;
+
+    #[test]
+    fn json_round_trip()
+    {
+        let top = TopSet::with_init(4, i32::gt, vec![81, -5, 4, 5, 4, 1, 45, 22, 1, 5, 97]);
+        let json = serde_json::to_string(&top).unwrap();
+        let decoded = TopSet::deserialize_with(&mut serde_json::Deserializer::from_str(&json), i32::gt).unwrap();
+        assert_eq![ decoded.capacity(), 4 ];
+        assert_eq![ decoded.into_sorted_vec(), top.into_sorted_vec() ];
+
+        // the order of a hand-edited file is not trusted
+        let json = r#"{ "heap": [9, 3, 7, 1, 8], "count": 3 }"#;
+        let mut decoded = TopSet::deserialize_with(&mut serde_json::Deserializer::from_str(json), i32::gt).unwrap();
+        assert_eq![ decoded.pop(), Some(7) ];
+        assert_eq![ decoded.pop(), Some(8) ];
+        assert_eq![ decoded.pop(), Some(9) ];
+
+        assert!( TopSet::deserialize_with(&mut serde_json::Deserializer::from_str("[1,2]"), i32::gt).is_err() );
+    }
+
+    #[test]
+    fn keyed_round_trip()
+    {
+        #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+        struct Run { name: String, time: f64 }
+
+        let by_time = |a: &Run, b: &Run| a.time < b.time;
+        let runs = [("ann", 9.8), ("bob", 10.2), ("eve", 9.6), ("joe", 11.0)]
+            .map(|(name, time)| Run { name: name.to_string(), time });
+        let top = TopSet::with_init(2, by_time, runs);
+
+        let json = serde_json::to_string(&top).unwrap();
+        let decoded = TopSet::deserialize_with(&mut serde_json::Deserializer::from_str(&json), by_time).unwrap();
+        let names = decoded.into_sorted_vec().into_iter().map(|r| r.name).collect::<Vec<_>>();
+        assert_eq![ names, vec!["ann", "eve"] ];
+    }
+
+    #[test]
+    #[cfg(feature = "bincode")]
+    fn sorted_bytes_round_trip()
+    {
+        let items = vec![81.5, 4.5, 4., 1., 45., 22., 11.];
+        let top = TopSet::with_init(4, f64::lt, items);
+        let bytes = top.to_sorted_bytes().unwrap();
+        assert_eq![ bytes.len(), 8 + 4*8 ];
+        assert_eq![ bincode::deserialize::<Vec<f64>>(&bytes).unwrap(), vec![1., 4., 4.5, 11.] ];
+
+        let decoded = TopSet::from_sorted_bytes(&bytes, 4, f64::lt).unwrap();
+        assert_eq![ decoded.into_sorted_vec(), top.into_sorted_vec() ];
+
+        let truncated = TopSet::from_sorted_bytes(&bytes, 2, f64::lt).unwrap();
+        assert_eq![ truncated.into_sorted_vec(), vec![4., 1.] ];
+
+        assert!( TopSet::<f64,_>::from_sorted_bytes(&bytes[..5], 2, f64::lt).is_err() );
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn json_leaderboard()
+    {
+        let scores = vec![("bob", 12), ("eve", 31), ("ann", 7), ("joe", 25), ("kim", 18)];
+        let top = TopSet::with_init(3, |a: &(&str, u32), b| a.1 > b.1, scores);
+        let json = top.to_json_array().unwrap();
+        let array = json.as_array().unwrap();
+        assert_eq![ array.len(), 3 ];
+        assert_eq![ json, serde_json::json!([["eve", 31], ["joe", 25], ["kim", 18]]) ];
+
+        let empty = TopSet::<u32,_>::new(3, u32::gt);
+        assert_eq![ empty.to_json_array().unwrap(), serde_json::json!([]) ];
+    }
+}
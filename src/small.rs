@@ -0,0 +1,200 @@
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+use core::mem;
+use smallvec::SmallVec;
+use crate::heap::HeapBuffer;
+
+/// A top N set of items stored inline for small sizes.
+///
+/// It behaves as [`crate::TopSet`] but the items are stored in a
+/// [`SmallVec`] which holds up to `K` items without any heap allocation.
+/// The number of kept items `n` is still dynamic: if it exceeds `K`,
+/// the storage spills to the heap as a regular vector.
+///
+/// This is useful when lots of short-lived small top sets are built.
+///
+/// # Example
+/// ```
+/// # use topset::SmallTopSet;
+/// let mut topset = SmallTopSet::<u32,_,4>::new(2, u32::gt);
+/// topset.extend(vec![7,5,6,9,4,2,3]);
+/// assert!( !topset.spilled() );
+/// assert_eq!( topset.into_sorted_vec(), vec![7,9]);
+/// ```
+#[derive(Clone)]
+pub struct SmallTopSet<X,C,const K: usize>
+    where C: Fn(&X,&X) -> bool
+{
+    heap: SmallVec<[X;K]>, // a heap with the greatest at the end
+    count: usize,
+    beat:  C
+}
+
+impl<X,C,const K: usize> SmallTopSet<X,C,K>
+    where C: Fn(&X,&X) -> bool
+{
+    /// Creates a new small top set with a selecting closure.
+    ///
+    /// See [`crate::TopSet::new`] for the meaning of the parameters.
+    pub fn new(n: usize, beat: C) -> Self
+    {
+        Self {
+            heap: SmallVec::with_capacity(n),
+            count: n,
+            beat
+        }
+    }
+
+    /// Creates a new small top set with a selecting closure and an initial set of items.
+    ///
+    /// See [`crate::TopSet::with_init`].
+    pub fn with_init<I: IntoIterator<Item=X>>(n: usize, beat: C, init: I) -> Self
+    {
+        let mut top = Self::new(n, beat);
+        top.extend(init);
+        top
+    }
+
+    /// Check if the top set is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.heap.is_empty() }
+
+    /// Get the number of stored items.
+    #[inline]
+    pub fn len(&self) -> usize { self.heap.len() }
+
+    /// Get the capacity of this top set
+    ///
+    /// This is the maximum number of kept items, not the inline capacity `K`.
+    #[inline]
+    pub fn capacity(&self) -> usize { self.count }
+
+    /// Checks if the items were moved from the inline storage to the heap.
+    #[inline]
+    pub fn spilled(&self) -> bool { self.heap.spilled() }
+
+    /// Read access to the lowest item of the top set
+    #[inline]
+    pub fn peek(&self) -> Option<&X>
+    {
+        self.heap.first()
+    }
+
+    /// Checks if an item will be inserted or not
+    #[inline]
+    pub fn is_candidate(&self, x: &X) -> bool {
+        self.heap.len() < self.count || self.peek().is_some_and(|p| (self.beat)(x, p))
+    }
+
+    /// Iterate over all the top selected items (**not** sorted).
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item=&X>
+    {
+        self.heap.iter()
+    }
+
+    /// Gets all the top set elements in a vector (**not** sorted).
+    #[inline]
+    pub fn into_vec(self) -> Vec<X> { self.heap.into_vec() }
+
+    /// Insert a new item.
+    ///
+    /// See [`crate::TopSet::insert`].
+    pub fn insert(&mut self, mut x: X) -> Option<X>
+    {
+        if self.heap.len() < self.count {
+            // some room left, so nothing to remove
+            self.heap.push(x);
+            self.percolate_up(self.heap.len()-1);
+            None
+        } else {
+            if self.count != 0 && (self.beat)(&x, &self.heap[0]) {
+                // put the greatest the deepest: the new one should be kept
+                mem::swap(&mut x, &mut self.heap[0]);
+                self.percolate_down(0);
+            }
+            Some(x)
+        }
+    }
+
+    /// Pop the lowest item of the top set
+    pub fn pop(&mut self) -> Option<X>
+    {
+        match self.heap.len() {
+            0 => None,
+            1|2 => Some(self.heap.swap_remove(0)),
+            _ => {
+                let pop = self.heap.swap_remove(0);
+                self.percolate_down(0);
+                Some(pop)
+            }
+        }
+    }
+
+    /// Returns the topset in a sorted vector.
+    ///
+    /// The first element of the vector is the _lowest_ item of the top set
+    /// and the last one is the _greatest_ one.
+    pub fn into_sorted_vec(mut self) -> Vec<X>
+    {
+        let mut sorted = Vec::with_capacity(self.heap.len());
+        while let Some(x) = self.pop() {
+            sorted.push(x);
+        }
+        sorted
+    }
+
+    /// Removes all the elements in the top set
+    #[inline] pub fn clear(&mut self) { self.heap.clear() }
+}
+
+impl<X,C,const K: usize> HeapBuffer for SmallTopSet<X,C,K>
+    where C: Fn(&X,&X) -> bool
+{
+    #[inline] fn size(&self) -> usize { self.heap.len() }
+    #[inline] fn duel(&self, i: usize, j: usize) -> bool { (self.beat)(&self.heap[i], &self.heap[j]) }
+    #[inline] fn exchange(&mut self, i: usize, j: usize) { self.heap.swap(i, j) }
+}
+
+impl<X,C,const K: usize> Extend<X> for SmallTopSet<X,C,K>
+    where C: Fn(&X,&X) -> bool
+{
+    #[inline]
+    fn extend<T: IntoIterator<Item=X>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|x| { self.insert(x); } )
+    }
+}
+
+impl<X,C,const K: usize> Debug for SmallTopSet<X,C,K>
+    where X:Debug, C: Fn(&X,&X) -> bool
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.heap.fmt(f)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{SmallTopSet, TopSet};
+
+    #[test]
+    fn inline_and_spilled()
+    {
+        let items = vec![81, 5, 4, 5, 4, 1, 45, 22, 1, 5, 97, 5, 877, 12, 0];
+
+        // below the inline capacity
+        let small = SmallTopSet::<u32,_,8>::with_init(5, u32::gt, items.clone());
+        assert!( !small.spilled() );
+        assert_eq![ small.len(), 5 ];
+        assert_eq![ small.into_sorted_vec(), TopSet::with_init(5, u32::gt, items.clone()).into_sorted_vec() ];
+
+        // above the inline capacity
+        let mut small = SmallTopSet::<u32,_,4>::with_init(10, u32::lt, items.clone());
+        assert!( small.spilled() );
+        assert_eq![ small.peek(), Some(&12) ];
+        assert_eq![ small.insert(100), Some(100) ];
+        assert_eq![ small.insert(2), Some(12) ];
+        assert_eq![ small.into_sorted_vec(), vec![5, 5, 5, 5, 4, 4, 2, 1, 1, 0] ];
+    }
+}
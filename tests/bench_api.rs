@@ -0,0 +1,35 @@
+#![cfg(feature = "bench-api")]
+use std::cell::Cell;
+use topset::bench::*;
+use topset::TopSet;
+
+#[test]
+fn comparisons_of_fixed_workloads()
+{
+    assert_eq![ workload(1000, 7), workload(1000, 7) ];
+
+    // these counts are the reference for the optimizations of the heap
+    assert_eq![ comparisons_for_workload(10, workload(1000, 7), u64::gt), 1210 ];
+    assert_eq![ comparisons_for_workload(10, ascending_workload(1000), u64::gt), 6015 ];
+    assert_eq![ comparisons_for_workload(10, ascending_workload(1000), u64::lt), 1009 ];
+    assert_eq![ comparisons_for_workload(0, ascending_workload(1000), u64::gt), 0 ];
+}
+
+#[test]
+fn percolation_hooks()
+{
+    let count = Cell::new(0);
+    let mut top = TopSet::rebuild(7, |a: &u64, b: &u64| { count.set(count.get() + 1); a > b }, workload(7, 1));
+
+    // a valid heap is left unchanged, with one challenge per level
+    count.set(0);
+    percolate_down(&mut top, 0);
+    assert_eq![ count.get(), 2 ];
+    count.set(0);
+    percolate_up(&mut top, 6);
+    assert_eq![ count.get(), 1 ];
+
+    let mut sorted = workload(7, 1);
+    sorted.sort();
+    assert_eq![ top.into_sorted_vec(), sorted ];
+}
@@ -0,0 +1,58 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::thread;
+use topset::iter::{DrainSorted, IntoIterSorted};
+use topset::{BoxedTopSet, TopSet};
+
+fn assert_send<T: Send>() {}
+fn assert_sync<T: Sync>() {}
+
+// the top sets are Send and Sync whenever their items and challenges are
+#[allow(dead_code)]
+fn generic_bounds<'a, X: Send + Sync + 'a, C: Fn(&X,&X) -> bool + Send + Sync + 'a>()
+{
+    assert_send::<TopSet<X,C>>();
+    assert_sync::<TopSet<X,C>>();
+    assert_send::<IntoIterSorted<X,C>>();
+    assert_sync::<IntoIterSorted<X,C>>();
+    assert_send::<DrainSorted<'a,X,C>>();
+    assert_sync::<DrainSorted<'a,X,C>>();
+}
+
+#[test]
+fn auto_traits()
+{
+    assert_send::<TopSet<String, fn(&String,&String) -> bool>>();
+    assert_sync::<TopSet<String, fn(&String,&String) -> bool>>();
+    assert_send::<BoxedTopSet<String>>();
+
+    let top = TopSet::new(3, |a: &u32, b: &u32| a > b);
+    fn sync_of<T: Sync>(_: &T) {}
+    sync_of(&top);
+    fn send_of<T: Send>(_: &T) {}
+    send_of(&top.into_iter_sorted());
+
+    // a challenge capturing a cell is Send but not Sync
+    let calls = Cell::new(0);
+    let counted = TopSet::new(3, move |a: &u32, b: &u32| { calls.set(calls.get() + 1); a > b });
+    send_of(&counted);
+}
+
+#[test]
+fn moved_and_shared_between_threads()
+{
+    let top = thread::spawn(|| TopSet::with_init(3, u32::gt, 0..100)).join().unwrap();
+    let sums = thread::scope(|s| {
+        let handles = (0..2).map(|_| s.spawn(|| top.iter().sum::<u32>())).collect::<Vec<_>>();
+        handles.into_iter().map(|h| h.join().unwrap()).collect::<Vec<_>>()
+    });
+    assert_eq![ sums, vec![294, 294] ];
+
+    let sorted = thread::spawn(move || top.into_iter_sorted().collect::<Vec<_>>()).join().unwrap();
+    assert_eq![ sorted, vec![97, 98, 99] ];
+
+    // the items of a non Send top set could still be moved out once extracted
+    let local = TopSet::with_init(2, |a: &Rc<u32>, b: &Rc<u32>| a > b, (0..5).map(Rc::new));
+    let plain = local.into_sorted_vec().into_iter().map(|x| *x).collect::<Vec<_>>();
+    assert_eq![ thread::spawn(move || plain).join().unwrap(), vec![3, 4] ];
+}